@@ -1,30 +1,48 @@
 use crate::blackjack::constants::basic_strategy_tables;
+pub mod charlie;
 pub mod constants;
+pub mod double_exposure;
+pub mod events;
+pub mod free_bet;
+pub mod hand_ev;
+pub mod history;
+pub mod house_edge;
+pub mod money;
+pub mod notation;
+pub mod pontoon;
+pub mod presets;
 pub mod ruleset;
-use crate::blackjack::constants::basic_strategy_tables::Strategy;
-use crate::blackjack::constants::UNSHUFFLED_DECK;
+pub mod scenario;
+pub mod side_bets;
+pub mod spanish21;
+pub mod switch;
+pub mod undo;
+use crate::blackjack::constants::basic_strategy_tables::{Strategy, StrategyTables};
 use core::panic;
+use events::{CardRecipient, GameEvent};
+use money::Money;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
-use ruleset::BlackjackRuleset;
+use ruleset::{BlackjackRuleset, GameVariant, RulesetProblem};
 use serde::{Deserialize, Serialize};
+use spanish21::BonusHand;
+use std::fmt;
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum Suit {
     Hearts,
     Diamonds,
     Clubs,
     Spades,
 }
-impl ToString for Suit {
-    fn to_string(&self) -> String {
-        match self {
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
             Suit::Hearts => "♥",
             Suit::Diamonds => "♦",
             Suit::Clubs => "♣",
             Suit::Spades => "♠",
-        }
-        .to_string()
+        })
     }
 }
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
@@ -43,9 +61,9 @@ pub enum Rank {
     King,
     Ace,
 }
-impl ToString for Rank {
-    fn to_string(&self) -> String {
-        match self {
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
             Rank::Two => "2",
             Rank::Three => "3",
             Rank::Four => "4",
@@ -59,43 +77,104 @@ impl ToString for Rank {
             Rank::Queen => "Q",
             Rank::King => "K",
             Rank::Ace => "A",
-        }
-        .to_string()
+        })
     }
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Card {
     pub suit: Suit,
     pub rank: Rank,
+    #[serde(default)]
     pub face_down: bool,
 }
-impl ToString for Card {
-    fn to_string(&self) -> String {
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self.face_down {
-            "?".to_string()
+            f.write_str("?")
         } else {
-            format!("{}{}", self.rank.to_string(), self.suit.to_string())
+            write!(f, "{}{}", self.rank, self.suit)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Hand {
+    pub cards: Vec<Card>,
+    #[serde(default)]
+    pub doubled: bool,
+    #[serde(default)]
+    pub from_split: bool,
+    #[serde(default)]
+    pub split_aces: bool, // started from a split pair of aces
+    #[serde(default)]
+    pub surrendered: bool,
+    #[serde(default)]
+    pub redoubles: u8, // Spanish 21 doubles after the first
+    #[serde(default)]
+    pub free_bet: Money, // part of the hand's bet paid for by the house
+    #[serde(default)]
+    pub switched: bool, // Blackjack Switch: second card came from the other hand
+    #[serde(default)]
+    pub bought: u8, // Pontoon: cards bought, each raising the bet by the original stake
+}
+impl Hand {
+    pub fn new() -> Hand {
+        Hand {
+            cards: Vec::with_capacity(8),
+            ..Hand::default()
         }
     }
+
+    pub fn is_pair(&self) -> bool {
+        self.cards.len() == 2 && self.cards[0].rank == self.cards[1].rank
+    }
+
+    pub fn is_pair_of_aces(&self) -> bool {
+        self.is_pair() && matches!(self.cards[0].rank, Rank::Ace)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
 pub enum GameState {
     Dealing,
+    // Blackjack Switch: after the deal, the player decides whether to switch
+    // before playing either hand
+    SwitchDecision,
     PlayerTurn,
     DealerTurn,
     GameOver,
 }
+impl GameState {
+    // Whether `next_state` needs a player action
+    pub fn awaits_player_action(&self) -> bool {
+        matches!(self, GameState::SwitchDecision | GameState::PlayerTurn)
+    }
+}
+
+// Returned by `try_next_state` when the shoe has no card for the next step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutOfCards;
+impl std::fmt::Display for OutOfCards {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("the shoe is out of cards")
+    }
+}
 #[derive(Serialize, Deserialize, Debug)]
 pub struct BlackjackState {
-    pub starting_bet: f32,
+    pub starting_bet: Money,
     pub shoe: Vec<Card>,
     pub dealer_hand: Vec<Card>,
-    pub player_hands: Vec<Vec<Card>>,
+    pub player_hands: Vec<Hand>,
     pub hand_index: usize,
-    pub bets: Vec<f32>,
+    pub bets: Vec<Money>,
     pub rules: BlackjackRuleset,
     pub state: GameState,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub side_bets: Vec<side_bets::SideBet>,
+    #[serde(skip)]
+    events: Option<Vec<GameEvent>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    undo_stack: Option<Vec<undo::Snapshot>>,
 }
 impl Clone for BlackjackState {
     fn clone(&self) -> Self {
@@ -106,59 +185,62 @@ impl Clone for BlackjackState {
             player_hands: self.player_hands.clone(),
             hand_index: self.hand_index,
             bets: self.bets.clone(),
-            rules: self.rules.clone(),
-            state: self.state.clone(),
+            rules: self.rules,
+            state: self.state,
+            side_bets: self.side_bets.clone(),
+            events: None,
+            undo_stack: self.undo_stack.clone(),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum PlayerAction {
     Hit,
     Stand,
     DoubleDown,
+    // doubling for less than the hand's bet, see `double_for_less`
+    DoubleFor(Money),
     Split,
     Surrender,
-}
-impl PartialEq for PlayerAction {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (PlayerAction::Hit, PlayerAction::Hit)
-            | (PlayerAction::Stand, PlayerAction::Stand)
-            | (PlayerAction::DoubleDown, PlayerAction::DoubleDown)
-            | (PlayerAction::Split, PlayerAction::Split)
-            | (PlayerAction::Surrender, PlayerAction::Surrender) => true,
-            _ => false,
-        }
-    }
+    Switch,
+    // Pontoon
+    Twist,
+    Stick,
+    Buy,
 }
 
 pub fn card_value(card: &Card, with_ace_as_11: bool) -> u8 {
     if card.face_down {
         0
     } else {
-        match card.rank {
-            Rank::Two => 2,
-            Rank::Three => 3,
-            Rank::Four => 4,
-            Rank::Five => 5,
-            Rank::Six => 6,
-            Rank::Seven => 7,
-            Rank::Eight => 8,
-            Rank::Nine => 9,
-            Rank::Ten | Rank::Jack | Rank::Queen | Rank::King => 10,
-            Rank::Ace => {
-                if with_ace_as_11 {
-                    11
-                } else {
-                    1
-                }
+        rank_value(&card.rank, with_ace_as_11)
+    }
+}
+
+// A card's value whether or not it is face down
+fn rank_value(rank: &Rank, with_ace_as_11: bool) -> u8 {
+    match rank {
+        Rank::Two => 2,
+        Rank::Three => 3,
+        Rank::Four => 4,
+        Rank::Five => 5,
+        Rank::Six => 6,
+        Rank::Seven => 7,
+        Rank::Eight => 8,
+        Rank::Nine => 9,
+        Rank::Ten | Rank::Jack | Rank::Queen | Rank::King => 10,
+        Rank::Ace => {
+            if with_ace_as_11 {
+                11
+            } else {
+                1
             }
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
 #[serde(tag = "kind", content = "value")]
 pub enum HandValue {
     Hard(u8),
@@ -166,54 +248,113 @@ pub enum HandValue {
     Blackjack,
 }
 use HandValue::*;
+impl fmt::Display for HandValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Hard(n) => write!(f, "hard {}", n),
+            Soft(n) => write!(f, "soft {}", n),
+            Blackjack => f.write_str("blackjack"),
+        }
+    }
+}
 
-const NUM_DECKS: usize = 8; // 8 gives huge increase to code execution speed for some reason
-pub fn init_state(starting_bet: f32, rules: BlackjackRuleset) -> BlackjackState {
-    let mut shoe: Vec<Card> = Vec::with_capacity(UNSHUFFLED_DECK.len() * NUM_DECKS);
-    for _ in 0..NUM_DECKS {
-        shoe.extend(UNSHUFFLED_DECK.iter().cloned());
+pub const NUM_DECKS: usize = 8; // 8 gives huge increase to code execution speed for some reason
+pub fn init_shoe(variant: GameVariant, num_decks: usize) -> Vec<Card> {
+    let deck = variant.deck();
+    let mut shoe: Vec<Card> = Vec::with_capacity(deck.len() * num_decks);
+    for _ in 0..num_decks {
+        shoe.extend(deck.iter().cloned());
     }
     shoe.shuffle(&mut thread_rng());
+    shoe
+}
+
+const MIN_CARDS_PER_ROUND: usize = 20;
+// reuses the shoe for the next round until the cut card is reached;
+// a penetration of 0 reshuffles before every round
+pub fn next_shoe(
+    shoe: Vec<Card>,
+    variant: GameVariant,
+    num_decks: usize,
+    penetration: f32,
+) -> Vec<Card> {
+    let total_cards = (num_decks * variant.deck().len()) as f32;
+    let cut_card = (total_cards * (1f32 - penetration)) as usize;
+    if shoe.len() <= cut_card || shoe.len() < MIN_CARDS_PER_ROUND {
+        init_shoe(variant, num_decks)
+    } else {
+        shoe
+    }
+}
+
+pub fn init_state(
+    starting_bet: Money,
+    rules: BlackjackRuleset,
+) -> Result<BlackjackState, Vec<RulesetProblem>> {
+    init_state_with_shoe(starting_bet, rules, init_shoe(rules.variant, NUM_DECKS))
+}
 
-    // #[rustfmt::skip]
-    // let debug_start = vec![
-    //     Card { suit: Suit::Hearts, rank: Rank::Two, face_down: false },
-    //     Card { suit: Suit::Hearts, rank: Rank::Four, face_down: false },
-    //     Card { suit: Suit::Hearts, rank: Rank::Two, face_down: false }, // first card
-    // ];
-    // shoe.extend(debug_start);
+/// Starts a round dealt from `shoe`. Cards are drawn from the end of the vector.
+/// The ruleset is used as given; contradictions are rejected rather than
+/// normalized away (see `BlackjackRuleset::normalize`).
+pub fn init_state_with_shoe(
+    starting_bet: Money,
+    rules: BlackjackRuleset,
+    shoe: Vec<Card>,
+) -> Result<BlackjackState, Vec<RulesetProblem>> {
+    rules.validate()?;
+    let num_hands = rules.variant.starting_hands();
     let mut player_hands = Vec::with_capacity(4);
-    player_hands.push(Vec::with_capacity(8));
-    BlackjackState {
+    player_hands.extend((0..num_hands).map(|_| Hand::new()));
+    Ok(BlackjackState {
         starting_bet,
         shoe,
         dealer_hand: Vec::with_capacity(8),
         player_hands,
         hand_index: 0,
-        bets: vec![starting_bet],
+        bets: vec![starting_bet; num_hands],
         rules,
         state: GameState::Dealing,
-    }
+        side_bets: Vec::new(),
+        events: None,
+        undo_stack: None,
+    })
+}
+
+// Net winnings of a finished round, after subtracting every bet placed
+pub fn round_net(game: &BlackjackState) -> Money {
+    let player_hand_outcomes = game.player_hand_outcomes();
+    game.bets
+        .iter()
+        .zip(&game.player_hands)
+        .zip(player_hand_outcomes)
+        .map(|((bet, hand), outcome)| outcome.net(*bet, hand.free_bet, &game.rules))
+        .sum()
 }
 
-fn bust(hand: &Vec<Card>) -> bool {
+fn bust(hand: &[Card]) -> bool {
     let value: u8 = hand.iter().map(|c| card_value(c, false)).sum();
     value > 21
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum WinReason {
     DealerBust,
     HigherHand,
-    Blackjack, // technically redundant but useful for displaying to user
+    Blackjack,     // technically redundant but useful for displaying to user
+    TwentyOne,     // Spanish 21: player 21 beats a dealer 21
+    Charlie,       // reached the ruleset's Charlie without busting
+    FiveCardTrick, // Pontoon: five cards without busting
+    Bonus(BonusHand),
 }
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum LossReason {
     Bust,
     LowerHand,
     DealerBlackjack, // technically redundant but useful for displaying to user
+    Tie,             // Double Exposure: the dealer wins ties
 }
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 #[serde(tag = "kind", content = "reason")]
 pub enum HandOutcome {
     Won(WinReason),
@@ -221,9 +362,26 @@ pub enum HandOutcome {
     Push,
     Surrendered,
 }
+impl HandOutcome {
+    // Net winnings of a hand with this outcome. `free_bet` is the part of the
+    // bet paid for by the house, which the player can't lose.
+    pub fn net(&self, bet: Money, free_bet: Money, rules: &BlackjackRuleset) -> Money {
+        match self {
+            HandOutcome::Won(WinReason::Blackjack) => bet.times(rules.blackjack_payout),
+            HandOutcome::Won(WinReason::Bonus(bonus)) => bet.times(bonus.payout()),
+            HandOutcome::Won(WinReason::FiveCardTrick) => {
+                bet.times(pontoon::FIVE_CARD_TRICK_PAYOUT)
+            }
+            HandOutcome::Won(_) => bet,
+            HandOutcome::Push => Money::ZERO,
+            HandOutcome::Lost(_) => free_bet - bet,
+            HandOutcome::Surrendered => bet.half() - bet,
+        }
+    }
+}
 
 impl BlackjackState {
-    fn hand_value_base(&self, _hand: &Vec<Card>, aces_split: bool, peek: bool) -> HandValue {
+    fn hand_value_base(&self, _hand: &[Card], split_aces: bool, peek: bool) -> HandValue {
         let hand = _hand
             .iter()
             .filter(|c| match peek {
@@ -234,19 +392,19 @@ impl BlackjackState {
         if hand.len() == 2 {
             let card1 = &hand[0].rank;
             let card2 = &hand[1].rank;
-            let is_blackjack = match (card1, card2, &self.rules.ace_and_ten_counts_as_blackjack) {
-                (Rank::Ace, Rank::Ten, true) => true,
-                (Rank::Ace, Rank::Jack, _) => true,
-                (Rank::Ace, Rank::Queen, _) => true,
-                (Rank::Ace, Rank::King, _) => true,
-                (Rank::Ten, Rank::Ace, true) => true,
-                (Rank::Jack, Rank::Ace, _) => true,
-                (Rank::Queen, Rank::Ace, _) => true,
-                (Rank::King, Rank::Ace, _) => true,
-                _ => false,
-            };
+            let is_blackjack = matches!(
+                (card1, card2, &self.rules.ace_and_ten_counts_as_blackjack),
+                (Rank::Ace, Rank::Ten, true)
+                    | (Rank::Ace, Rank::Jack, _)
+                    | (Rank::Ace, Rank::Queen, _)
+                    | (Rank::Ace, Rank::King, _)
+                    | (Rank::Ten, Rank::Ace, true)
+                    | (Rank::Jack, Rank::Ace, _)
+                    | (Rank::Queen, Rank::Ace, _)
+                    | (Rank::King, Rank::Ace, _)
+            );
             if is_blackjack {
-                return if aces_split && !&self.rules.split_ace_can_be_blackjack {
+                return if split_aces && !&self.rules.split_ace_can_be_blackjack {
                     Hard(21)
                 } else {
                     Blackjack
@@ -254,64 +412,44 @@ impl BlackjackState {
             }
         }
         let has_ace = hand.iter().any(|c| matches!(c.rank, Rank::Ace));
-        let low_val: u8 = hand.iter().map(|c| card_value(c, false)).sum();
+        let low_val: u8 = hand.iter().map(|c| rank_value(&c.rank, false)).sum();
         match has_ace && low_val <= 11 {
             true => Soft(low_val + 10),
             false => Hard(low_val),
         }
     }
 
-    pub fn player_hand_value(&self, hand: &Vec<Card>, aces_split: bool) -> HandValue {
-        self.hand_value_base(hand, aces_split, false)
+    pub fn player_hand_value(&self, hand: &Hand) -> HandValue {
+        match self.hand_value_base(&hand.cards, hand.split_aces, false) {
+            Blackjack if hand.switched => Hard(21), // Blackjack Switch
+            value => value,
+        }
     }
 
-    pub fn dealer_hand_value(&self, hand: &Vec<Card>, peek: bool) -> HandValue {
+    pub fn dealer_hand_value(&self, hand: &[Card], peek: bool) -> HandValue {
         self.hand_value_base(hand, false, peek)
     }
 
-    fn aces_split(&self, player_hands: &Vec<Vec<Card>>) -> bool {
-        player_hands.len() > 1
-            && player_hands
-                .iter()
-                .all(|hand| matches!(hand[0].rank, Rank::Ace))
-    }
-
-    fn next_split_hand_index(&self, player_hands: &Vec<Vec<Card>>) -> usize {
-        match player_hands
-            .iter()
-            .skip(self.hand_index + 1)
-            .position(|hand| hand.len() == 1)
-        {
-            Some(index) => self.hand_index + 1 + index,
-            None => self.hand_index,
+    // Hands after the current one are still to be played: split hands are
+    // waiting for their second card, a Blackjack Switch hand was dealt whole
+    fn next_hand_index(&self, player_hands: &[Hand]) -> usize {
+        match self.hand_index + 1 < player_hands.len() {
+            true => self.hand_index + 1,
+            false => self.hand_index,
         }
     }
 
-    pub fn player_split_aces(&self, player_hands: &Vec<Vec<Card>>) -> bool {
-        player_hands.len() >= 2
-            && player_hands
-                .iter()
-                .all(|hand| matches!(hand[0].rank, Rank::Ace))
-    }
-
-    fn player_hand_finished(&self, player_hands: &Vec<Vec<Card>>) -> bool {
+    pub(crate) fn player_hand_finished(&self, player_hands: &[Hand]) -> bool {
         let player_hand = &player_hands[self.hand_index];
-        let player_hand_value =
-            self.player_hand_value(player_hand, self.player_split_aces(player_hands));
-        let pair_of_aces = player_hand.len() == 2
-            && player_hand
-                .iter()
-                .all(|card| matches!(card.rank, Rank::Ace));
+        let player_hand_value = self.player_hand_value(player_hand);
         let cannot_resplit_ace = match &self.rules.split_aces {
             ruleset::SplitAces::NotAllowed | ruleset::SplitAces::Once => true, // cannot split ace
             ruleset::SplitAces::Twice => self.player_hands.len() >= 3,
             ruleset::SplitAces::Thrice => self.player_hands.len() >= 4,
         };
-        let split_ace_finished = if !self.player_split_aces(player_hands) {
-            false
-        } else if self.rules.hit_on_split_ace {
+        let split_ace_finished = if !player_hand.split_aces || self.rules.hit_on_split_ace {
             false // this hand is not finished; player can hit or split aces
-        } else if pair_of_aces {
+        } else if player_hand.is_pair_of_aces() {
             cannot_resplit_ace // player may not hit but might be able to split aces
         } else {
             true // this hand is finished (eg. A, 5)
@@ -325,36 +463,69 @@ impl BlackjackState {
             _ => false,
         };
         let blackjack = matches!(player_hand_value, Blackjack);
-        bust(player_hand) || split_ace_finished || twenty_one || soft_twenty_one || blackjack
+        let charlie = self
+            .rules
+            .charlie
+            .is_some_and(|cards| player_hand.cards.len() >= cards as usize);
+        let five_card_trick =
+            self.is_pontoon() && player_hand.cards.len() >= pontoon::FIVE_CARD_TRICK;
+        bust(&player_hand.cards)
+            || split_ace_finished
+            || twenty_one
+            || soft_twenty_one
+            || blackjack
+            || charlie
+            || five_card_trick
+    }
+
+    // Whether a hand's two cards form a pair, which with `split_unlike_tens`
+    // is any two ten-value cards
+    fn is_splittable_pair(&self, hand: &Hand) -> bool {
+        let ten_values =
+            hand.cards.len() == 2 && hand.cards.iter().all(|card| card_value(card, false) == 10);
+        hand.is_pair() || (self.rules.split_unlike_tens && ten_values)
     }
 
     pub fn allowed_actions(&self) -> Vec<PlayerAction> {
+        if matches!(&self.state, GameState::SwitchDecision) {
+            // standing keeps the hands as they were dealt
+            return vec![PlayerAction::Stand, PlayerAction::Switch];
+        }
         if !matches!(&self.state, GameState::PlayerTurn) {
             panic!("Invalid state: {:?}", &self.state);
         }
 
         let player_hand = &self.player_hands[self.hand_index];
-        let player_hand_value =
-            self.player_hand_value(player_hand, self.player_split_aces(&self.player_hands));
+        let player_hand_value = self.player_hand_value(player_hand);
         if self.player_hand_finished(&self.player_hands) {
             dbg!(self);
             panic!("Player hand is finished; no allowed actions on this hand.");
         }
+        if player_hand.doubled {
+            if !matches!(self.rules.variant, GameVariant::Spanish21) {
+                return vec![PlayerAction::Stand];
+            }
+            // Spanish 21: a doubled hand is still played, it can be redoubled
+            // or surrendered, forfeiting only the original bet
+            let mut allowed_actions = vec![PlayerAction::Stand];
+            if player_hand.redoubles < spanish21::MAX_REDOUBLES {
+                allowed_actions.push(PlayerAction::DoubleDown);
+            }
+            if self.rules.surrender && self.player_hands.len() == 1 {
+                allowed_actions.push(PlayerAction::Surrender);
+            }
+            return allowed_actions;
+        }
 
-        let can_hit = if self.rules.hit_on_split_ace {
-            true
-        } else {
-            !self.aces_split(&self.player_hands)
-        };
+        let can_hit = !player_hand.split_aces || self.rules.hit_on_split_ace;
 
         let can_split = {
-            let is_pair = player_hand.len() == 2 && player_hand[0].rank == player_hand[1].rank;
             let can_split_aces = {
-                let num_aces_split = if self.aces_split(&self.player_hands) {
-                    self.player_hands.len()
-                } else {
-                    0
-                };
+                let num_aces_split = self
+                    .player_hands
+                    .iter()
+                    .filter(|hand| hand.split_aces)
+                    .count();
                 match &self.rules.split_aces {
                     ruleset::SplitAces::NotAllowed => false,
                     ruleset::SplitAces::Once => num_aces_split < 1,
@@ -368,13 +539,29 @@ impl BlackjackState {
                 ruleset::MaxHandsAfterSplit::Three => self.player_hands.len() < 3,
                 ruleset::MaxHandsAfterSplit::Four => self.player_hands.len() < 4,
             };
-            is_pair
+            let can_split_pair = || {
+                let pair_value = card_value(&player_hand.cards[0], false);
+                // a split leaves two hands starting with the pair's card
+                let num_pairs_split = self
+                    .player_hands
+                    .iter()
+                    .filter(|hand| {
+                        hand.from_split && card_value(&hand.cards[0], false) == pair_value
+                    })
+                    .count()
+                    .saturating_sub(1);
+                num_pairs_split < self.rules.pair_splits.max_splits(pair_value)
+            };
+            self.is_splittable_pair(player_hand)
                 && house_rule_satisfied
-                && match player_hand[0].rank {
+                && match player_hand.cards[0].rank {
                     Rank::Ace => can_split_aces,
-                    _ => true,
+                    _ => can_split_pair(),
                 }
         };
+        if self.is_pontoon() {
+            return self.pontoon_actions(can_hit, can_split);
+        }
         let can_double_down = {
             let house_rule_satisfied = match &self.rules.double_down_on {
                 ruleset::DoubleDownOn::Any => true,
@@ -385,15 +572,29 @@ impl BlackjackState {
                     matches!(player_hand_value, Hard(10) | Hard(11))
                 }
             };
-            player_hand.len() == 2
+            let split_rule_satisfied = match (player_hand.from_split, player_hand.split_aces) {
+                (false, _) => true,
+                (true, false) => self.rules.double_after_split,
+                (true, true) => self.rules.double_on_split_ace,
+            };
+            let max_cards = self
+                .rules
+                .multi_card_double
+                .map_or(2, |cards| cards as usize);
+            player_hand.cards.len() <= max_cards
                 && house_rule_satisfied
-                && !player_hand.iter().all(|c| matches!(c.rank, Rank::Ace))
+                && split_rule_satisfied
+                && !player_hand.is_pair_of_aces()
         };
 
+        // late surrender, once the dealer has checked for a blackjack
+        let dealer_checked = card_value(&self.dealer_hand[0], true) < 10 || self.dealer_peeked();
         let can_surrender = self.rules.surrender
             && self.player_hands.len() == 1
-            && self.player_hands[0].len() == 2
-            && self.dealer_hand[1].face_down;
+            && player_hand.cards.len() == 2
+            && !player_hand.from_split
+            && self.dealer_hand[1].face_down
+            && dealer_checked;
 
         let mut allowed_actions: Vec<PlayerAction> = Vec::with_capacity(4);
         if can_hit {
@@ -412,32 +613,146 @@ impl BlackjackState {
         allowed_actions
     }
 
+    // The most the current hand can be doubled for with `DoubleFor`, which
+    // isn't listed in `allowed_actions` as it carries an amount. Doubling for
+    // less is allowed wherever doubling is, for any amount up to the hand's bet.
+    pub fn max_double_for(&self) -> Option<Money> {
+        match self.rules.double_for_less
+            && self.allowed_actions().contains(&PlayerAction::DoubleDown)
+        {
+            true => Some(self.bets[self.hand_index]),
+            false => None,
+        }
+    }
+
+    // Whether the action can be taken on the current hand
+    pub fn is_action_allowed(&self, action: PlayerAction) -> bool {
+        match action {
+            PlayerAction::DoubleFor(amount) => self
+                .max_double_for()
+                .is_some_and(|max| amount > Money::ZERO && amount <= max),
+            _ => self.allowed_actions().contains(&action),
+        }
+    }
+
+    // Finds the current hand's row in the strategy tables, `column` being the
+    // dealer's (eg. the up card)
+    fn lookup_strategy<'a, const N: usize>(
+        &self,
+        (hard, soft, split): StrategyTables<'a, N>,
+        column: usize,
+        can_split: bool,
+    ) -> &'a Strategy {
+        let player_hand = &self.player_hands[self.hand_index];
+        if can_split {
+            let card_value = card_value(&player_hand.cards[0], true);
+            return &split[card_value as usize - 2][column];
+        }
+        match self.player_hand_value(player_hand) {
+            Hard(n) => {
+                if n < 8 {
+                    &hard[0][column]
+                } else {
+                    &hard[n as usize - 8][column]
+                }
+            }
+            Soft(n) => &soft[n as usize - 12][column],
+            Blackjack => {
+                panic!("Unreachable code.")
+            }
+        }
+    }
+
     pub fn get_optimal_move(&self) -> PlayerAction {
+        if matches!(self.state, GameState::SwitchDecision) {
+            return match self.should_switch() {
+                true => PlayerAction::Switch,
+                false => PlayerAction::Stand,
+            };
+        }
         let allowed_actions = self.allowed_actions();
+        if self.is_pontoon() {
+            // no dealer card is visible
+            return self.pontoon_optimal_move(&allowed_actions);
+        }
         let dealer_upcard = &self.dealer_hand[0];
         let dealer_upcard = card_value(dealer_upcard, true);
         let player_hand = &self.player_hands[self.hand_index];
         let can_split = allowed_actions.contains(&PlayerAction::Split);
-        let strategy = if can_split {
-            let card_value = card_value(&player_hand[0], true);
-            &basic_strategy_tables::SPLIT[card_value as usize - 2][dealer_upcard as usize - 2]
-        } else {
-            match self.player_hand_value(&self.player_hands[self.hand_index], false) {
-                Hard(n) => {
-                    if n < 8 {
-                        &basic_strategy_tables::HARD[0][dealer_upcard as usize - 2]
-                    } else {
-                        &basic_strategy_tables::HARD[n as usize - 8][dealer_upcard as usize - 2]
-                    }
-                }
-                Soft(n) => {
-                    &basic_strategy_tables::SOFT[n as usize - 12][dealer_upcard as usize - 2]
+        let column = dealer_upcard as usize - 2;
+        let strategy = match self.rules.variant {
+            GameVariant::Standard => self.lookup_strategy(
+                (
+                    &basic_strategy_tables::HARD,
+                    &basic_strategy_tables::SOFT,
+                    &basic_strategy_tables::SPLIT,
+                ),
+                column,
+                can_split,
+            ),
+            GameVariant::Spanish21 => self.lookup_strategy(
+                (
+                    &basic_strategy_tables::spanish21::HARD,
+                    &basic_strategy_tables::spanish21::SOFT,
+                    &basic_strategy_tables::spanish21::SPLIT,
+                ),
+                column,
+                can_split,
+            ),
+            GameVariant::FreeBet => self.lookup_strategy(
+                (
+                    &basic_strategy_tables::free_bet::HARD,
+                    &basic_strategy_tables::free_bet::SOFT,
+                    &basic_strategy_tables::free_bet::SPLIT,
+                ),
+                column,
+                can_split,
+            ),
+            GameVariant::Switch => self.lookup_strategy(
+                (
+                    &basic_strategy_tables::switch::HARD,
+                    &basic_strategy_tables::switch::SOFT,
+                    &basic_strategy_tables::switch::SPLIT,
+                ),
+                column,
+                can_split,
+            ),
+            // looked up by the dealer's total rather than the up card
+            GameVariant::DoubleExposure => self.double_exposure_strategy(can_split),
+            GameVariant::Pontoon => panic!("Unreachable code."),
+        };
+        // an unchecked ten or ace may still be a blackjack, which takes the
+        // doubled and split bets too, so only aces are split against a ten
+        let dealer_checked = self.dealer_peeked() || self.hole_card_exposed();
+        let strategy = match strategy {
+            _ if dealer_upcard < 10 || dealer_checked => strategy,
+            Strategy::P if player_hand.is_pair_of_aces() && dealer_upcard == 10 => strategy,
+            Strategy::D | Strategy::P | Strategy::PH => &Strategy::H,
+            Strategy::DS => &Strategy::S,
+            _ => strategy,
+        };
+        if matches!(strategy, Strategy::S | Strategy::DS)
+            && allowed_actions.contains(&PlayerAction::Hit)
+            && self.charlie_hit_beats_stand()
+        {
+            return PlayerAction::Hit;
+        }
+        if player_hand.doubled {
+            // a doubled hand can't be hit; rather than stand on a hand worth
+            // hitting against a strong up card, give up the original bet
+            return match strategy {
+                Strategy::D | Strategy::DS
+                    if allowed_actions.contains(&PlayerAction::DoubleDown) =>
+                {
+                    PlayerAction::DoubleDown
                 }
-                Blackjack => {
-                    panic!("Unreachable code.")
+                Strategy::S | Strategy::DS => PlayerAction::Stand,
+                _ if allowed_actions.contains(&PlayerAction::Surrender) && dealer_upcard >= 9 => {
+                    PlayerAction::Surrender
                 }
-            }
-        };
+                _ => PlayerAction::Stand,
+            };
+        }
         match strategy {
             Strategy::H => match allowed_actions.contains(&PlayerAction::Hit) {
                 true => PlayerAction::Hit,
@@ -464,61 +779,231 @@ impl BlackjackState {
         }
     }
 
-    pub fn next_state(&mut self, player_action: Option<PlayerAction>) -> () {
+    // Whether the dealer checks for a blackjack under the up card (also when
+    // it's face down, as in Pontoon)
+    pub fn dealer_peeked(&self) -> bool {
+        let upcard = Card {
+            face_down: false,
+            ..self.dealer_hand[0].clone()
+        };
+        self.rules
+            .dealer_peeks
+            .peeks_under(card_value(&upcard, true))
+    }
+
+    fn dealer_should_stand(&self) -> bool {
+        if self.is_pontoon() && self.dealer_hand.len() >= pontoon::FIVE_CARD_TRICK {
+            return true;
+        }
+        self.rules
+            .effective_dealer_policy()
+            .stands_on(self.dealer_hand_value(&self.dealer_hand, false))
+    }
+
+    pub fn next_state(&mut self, player_action: Option<PlayerAction>) {
+        let previous_state = self.state;
+        let previous_hand_index = self.hand_index;
+        if player_action.is_some() {
+            self.push_undo_snapshot();
+        }
+        self.advance(player_action)
+            .expect("Shoe is out of cards; use try_next_state to check for it.");
+        if matches!(self.state, GameState::GameOver) {
+            self.settle_dealer_side_bets();
+        }
+        if self.events.is_some() {
+            self.emit_transition_events(previous_state, previous_hand_index);
+        }
+    }
+
+    pub fn next_state_with_events(
+        &mut self,
+        player_action: Option<PlayerAction>,
+    ) -> Vec<GameEvent> {
+        self.events = Some(Vec::new());
+        self.next_state(player_action);
+        self.events.take().unwrap_or_default()
+    }
+
+    // `next_state` for a shoe that may run out, like a recorded one: a step
+    // that needs a card the shoe doesn't have is refused and leaves the game
+    // as it was
+    pub fn try_next_state(
+        &mut self,
+        player_action: Option<PlayerAction>,
+    ) -> Result<(), OutOfCards> {
+        // a step draws at most one card, so only an empty shoe can run out;
+        // the step is tried on a copy first
+        if self.shoe.is_empty() {
+            self.clone().advance(player_action)?;
+        }
+        self.next_state(player_action);
+        Ok(())
+    }
+
+    pub fn try_next_state_with_events(
+        &mut self,
+        player_action: Option<PlayerAction>,
+    ) -> Result<Vec<GameEvent>, OutOfCards> {
+        self.events = Some(Vec::new());
+        let result = self.try_next_state(player_action);
+        let events = self.events.take().unwrap_or_default();
+        result.map(|()| events)
+    }
+
+    fn draw(&mut self) -> Result<Card, OutOfCards> {
+        self.shoe.pop().ok_or(OutOfCards)
+    }
+
+    pub fn next_state_with_listener(
+        &mut self,
+        player_action: Option<PlayerAction>,
+        mut listener: impl FnMut(&GameEvent),
+    ) {
+        for event in self.next_state_with_events(player_action) {
+            listener(&event);
+        }
+    }
+
+    fn emit(&mut self, event: GameEvent) {
+        if let Some(events) = &mut self.events {
+            events.push(event);
+        }
+    }
+
+    fn emit_card_dealt(&mut self, recipient: CardRecipient) {
+        if self.events.is_some() {
+            let card = match recipient {
+                CardRecipient::Player { hand_index } => &self.player_hands[hand_index].cards,
+                CardRecipient::Dealer => &self.dealer_hand,
+            }
+            .last()
+            .filter(|card| !card.face_down)
+            .cloned();
+            self.emit(GameEvent::CardDealt { recipient, card });
+        }
+    }
+
+    // events that follow from the state change rather than a single step
+    fn emit_transition_events(&mut self, previous_state: GameState, previous_hand_index: usize) {
+        let was_playing_hand = matches!(
+            previous_state,
+            GameState::Dealing | GameState::SwitchDecision | GameState::PlayerTurn
+        );
+        let left_hand = previous_hand_index != self.hand_index
+            || matches!(self.state, GameState::DealerTurn | GameState::GameOver);
+        if was_playing_hand && left_hand {
+            let value = self.player_hand_value(&self.player_hands[previous_hand_index]);
+            self.emit(GameEvent::HandFinished {
+                hand_index: previous_hand_index,
+                value,
+            });
+        }
+        if matches!(self.state, GameState::GameOver) {
+            let outcomes = self.player_hand_outcomes();
+            self.emit(GameEvent::RoundSettled { outcomes });
+        }
+    }
+
+    // Deals the dealer's face down card, which ends the deal
+    fn deal_hole_card(&mut self) -> Result<(), OutOfCards> {
+        let dealer_card = self.draw()?;
+        self.dealer_hand.push(Card {
+            face_down: !self.hole_card_exposed(),
+            ..dealer_card
+        });
+        self.emit_card_dealt(CardRecipient::Dealer);
+        self.settle_side_bets();
+        let dealer_hand_value = self.dealer_hand_value(&self.dealer_hand, true);
+        let dealer_checks = self.dealer_peeked() || self.hole_card_exposed();
+        if dealer_checks && matches!(dealer_hand_value, Blackjack) {
+            self.state = GameState::DealerTurn;
+        } else if self.rules.variant == GameVariant::Switch {
+            // the player decides whether to switch even when holding a 21
+            self.state = GameState::SwitchDecision;
+        } else {
+            match self.player_hand_value(&self.player_hands[0]) {
+                Blackjack | Hard(21) | Soft(21) => {
+                    self.state = GameState::DealerTurn;
+                }
+                _ => {
+                    self.state = GameState::PlayerTurn;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn advance(&mut self, player_action: Option<PlayerAction>) -> Result<(), OutOfCards> {
         match self.state {
+            GameState::Dealing
+                if self.dealer_hand.len() < 2 && self.rules.variant == GameVariant::Switch =>
+            {
+                self.deal_switch_card()?;
+            }
             GameState::Dealing => match (
                 self.dealer_hand.len(),
                 (
-                    self.player_hands.get(0).unwrap_or(&vec![]).len(),
-                    self.player_hands.get(1).unwrap_or(&vec![]).len(),
-                    self.player_hands.get(2).unwrap_or(&vec![]).len(),
-                    self.player_hands.get(3).unwrap_or(&vec![]).len(),
+                    self.player_hands.first().map_or(0, |hand| hand.cards.len()),
+                    self.player_hands.get(1).map_or(0, |hand| hand.cards.len()),
+                    self.player_hands.get(2).map_or(0, |hand| hand.cards.len()),
+                    self.player_hands.get(3).map_or(0, |hand| hand.cards.len()),
                 ),
             ) {
                 (0, (0, 0, 0, 0)) => {
                     // deal first card to player
-                    let player_card = self.shoe.pop().unwrap();
-                    self.player_hands[0].push(player_card);
+                    let player_card = self.draw()?;
+                    self.player_hands[0].cards.push(player_card);
+                    self.emit_card_dealt(CardRecipient::Player { hand_index: 0 });
                 }
                 (0, (1, 0, 0, 0)) => {
-                    // deal second card to dealer
-                    let dealer_card = self.shoe.pop().unwrap();
-                    self.dealer_hand.push(dealer_card);
+                    // deal second card to dealer (face down in Pontoon)
+                    let dealer_card = self.draw()?;
+                    self.dealer_hand.push(Card {
+                        face_down: self.is_pontoon(),
+                        ..dealer_card
+                    });
+                    self.emit_card_dealt(CardRecipient::Dealer);
                 }
                 (1, (1, 0, 0, 0)) => {
                     // deal third card to player
-                    let player_card = self.shoe.pop().unwrap();
-                    self.player_hands[0].push(player_card);
+                    let player_card = self.draw()?;
+                    self.player_hands[0].cards.push(player_card);
+                    self.emit_card_dealt(CardRecipient::Player { hand_index: 0 });
                 }
                 (1, (2, 0, 0, 0)) => {
                     // deal fourth card to dealer (face down)
-                    let dealer_card = self.shoe.pop().unwrap();
-                    self.dealer_hand.push(Card {
-                        face_down: true,
-                        ..dealer_card
-                    });
-                    let dealer_hand_value = self.dealer_hand_value(&self.dealer_hand, true);
-                    if self.rules.dealer_peeks && matches!(dealer_hand_value, Blackjack) {
-                        self.state = GameState::DealerTurn;
+                    self.deal_hole_card()?;
+                }
+                _ if self.dealer_hand.len() == 2
+                    && self.player_hands[self.hand_index].cards.len() >= 2 =>
+                {
+                    // Blackjack Switch: the next hand was dealt with the first
+                    let player_hand_finished = self.player_hand_finished(&self.player_hands);
+                    let hand_index = match player_hand_finished {
+                        true => self.next_hand_index(&self.player_hands),
+                        false => self.hand_index,
+                    };
+                    self.state = if !player_hand_finished {
+                        GameState::PlayerTurn
+                    } else if hand_index != self.hand_index {
+                        GameState::Dealing
                     } else {
-                        match self.player_hand_value(&self.player_hands[0], false) {
-                            Blackjack | Hard(21) | Soft(21) => {
-                                self.state = GameState::DealerTurn;
-                            }
-                            _ => {
-                                self.state = GameState::PlayerTurn;
-                            }
-                        }
-                    }
+                        GameState::DealerTurn
+                    };
+                    self.hand_index = hand_index;
                 }
                 (_, (_, 1, _, _)) | (_, (_, _, 1, _)) | (_, (_, _, _, 1)) => {
                     // player just split, deal 1 card
                     // note: bust impossible no need to check
-                    let player_card = self.shoe.pop().unwrap();
-                    self.player_hands[self.hand_index].push(player_card);
+                    let player_card = self.draw()?;
+                    self.player_hands[self.hand_index].cards.push(player_card);
+                    self.emit_card_dealt(CardRecipient::Player {
+                        hand_index: self.hand_index,
+                    });
                     let player_hand_finished = self.player_hand_finished(&self.player_hands);
                     let hand_index = match player_hand_finished {
-                        true => self.next_split_hand_index(&self.player_hands),
+                        true => self.next_hand_index(&self.player_hands),
                         false => self.hand_index,
                     };
                     let switching_to_split_hand = hand_index != self.hand_index;
@@ -536,28 +1021,64 @@ impl BlackjackState {
                     panic!("Unreachable code: {:?}", self);
                 }
             },
+            GameState::SwitchDecision => {
+                let player_action = player_action.unwrap();
+                if !self.is_action_allowed(player_action) {
+                    panic!(
+                        "Invalid action: {:?}. Valid actions are {:?}",
+                        player_action,
+                        self.allowed_actions()
+                    );
+                }
+                self.emit(GameEvent::ActionTaken {
+                    hand_index: self.hand_index,
+                    action: player_action,
+                });
+                if matches!(player_action, PlayerAction::Switch) {
+                    self.switch_hands();
+                }
+                self.state = match self.player_hand_finished(&self.player_hands) {
+                    true => {
+                        // the first hand is a 21, move on to the second hand
+                        self.hand_index = self.next_hand_index(&self.player_hands);
+                        GameState::Dealing
+                    }
+                    false => GameState::PlayerTurn,
+                };
+            }
             GameState::PlayerTurn => {
-                let allowed_actions = self.allowed_actions();
                 let player_action = player_action.unwrap();
-                if !allowed_actions.contains(&player_action) {
-                    dbg!(self);
+                if !self.is_action_allowed(player_action) {
+                    dbg!(&self);
                     panic!(
                         "Invalid action: {:?}. Valid actions are {:?}",
-                        player_action, allowed_actions
+                        player_action,
+                        self.allowed_actions()
                     );
                 }
+                self.emit(GameEvent::ActionTaken {
+                    hand_index: self.hand_index,
+                    action: player_action,
+                });
                 match player_action {
-                    PlayerAction::Hit => {
-                        let player_card = self.shoe.pop().unwrap();
-                        self.player_hands[self.hand_index].push(player_card);
+                    PlayerAction::Hit | PlayerAction::Twist | PlayerAction::Buy => {
+                        if matches!(player_action, PlayerAction::Buy) {
+                            self.pay_for_bought_card();
+                        }
+                        let player_card = self.draw()?;
+                        self.player_hands[self.hand_index].cards.push(player_card);
+                        self.emit_card_dealt(CardRecipient::Player {
+                            hand_index: self.hand_index,
+                        });
                         let player_hand_finished = self.player_hand_finished(&self.player_hands);
                         let hand_index = if player_hand_finished {
-                            self.next_split_hand_index(&self.player_hands)
+                            self.next_hand_index(&self.player_hands)
                         } else {
                             self.hand_index
                         };
                         let switching_to_split_hand = hand_index != self.hand_index;
-                        let state = if self.player_hands.iter().all(|hand| bust(hand)) {
+                        let all_bust = self.player_hands.iter().all(|hand| bust(&hand.cards));
+                        let state = if all_bust && !self.dealer_must_play_out() {
                             GameState::GameOver
                         } else {
                             match (player_hand_finished, switching_to_split_hand) {
@@ -569,8 +1090,8 @@ impl BlackjackState {
                         self.hand_index = hand_index;
                         self.state = state;
                     }
-                    PlayerAction::Stand => {
-                        let hand_index = self.next_split_hand_index(&self.player_hands);
+                    PlayerAction::Stand | PlayerAction::Stick => {
+                        let hand_index = self.next_hand_index(&self.player_hands);
                         let switching_to_split_hand = hand_index != self.hand_index;
                         match switching_to_split_hand {
                             true => {
@@ -582,15 +1103,42 @@ impl BlackjackState {
                             }
                         }
                     }
-                    PlayerAction::DoubleDown => {
-                        self.bets[self.hand_index] *= 2.0;
+                    PlayerAction::DoubleDown | PlayerAction::DoubleFor(_) => {
+                        let bet = self.bets[self.hand_index];
+                        let amount = match player_action {
+                            PlayerAction::DoubleFor(amount) => amount,
+                            _ => bet,
+                        };
+                        if self.double_is_free() && amount == bet {
+                            self.player_hands[self.hand_index].free_bet += bet;
+                        }
+                        self.bets[self.hand_index] = bet + amount;
+                        let hand = &mut self.player_hands[self.hand_index];
+                        if hand.doubled {
+                            hand.redoubles += 1;
+                        }
+                        hand.doubled = true;
+                        self.emit(GameEvent::HandDoubled {
+                            hand_index: self.hand_index,
+                            bet: self.bets[self.hand_index],
+                        });
 
-                        let player_card = self.shoe.pop().unwrap();
-                        self.player_hands[self.hand_index].push(player_card);
-                        let hand_index = self.next_split_hand_index(&self.player_hands);
+                        let player_card = self.draw()?;
+                        self.player_hands[self.hand_index].cards.push(player_card);
+                        self.emit_card_dealt(CardRecipient::Player {
+                            hand_index: self.hand_index,
+                        });
+                        if matches!(self.rules.variant, GameVariant::Spanish21)
+                            && !self.player_hand_finished(&self.player_hands)
+                        {
+                            // the player may still redouble or surrender
+                            return Ok(());
+                        }
+                        let hand_index = self.next_hand_index(&self.player_hands);
                         let switching_to_split_hand = hand_index != self.hand_index;
                         self.hand_index = hand_index;
-                        self.state = if self.player_hands.iter().all(|hand| bust(hand)) {
+                        let all_bust = self.player_hands.iter().all(|hand| bust(&hand.cards));
+                        self.state = if all_bust && !self.dealer_must_play_out() {
                             GameState::GameOver
                         } else if switching_to_split_hand {
                             GameState::Dealing
@@ -599,50 +1147,78 @@ impl BlackjackState {
                         };
                     }
                     PlayerAction::Split => {
+                        let free_bet = match self.split_is_free() {
+                            true => self.starting_bet,
+                            false => Money::ZERO,
+                        };
                         self.bets.push(self.starting_bet);
-                        let card2 = self.player_hands[self.hand_index].pop().unwrap();
-                        let mut new_hand = Vec::with_capacity(8);
-                        new_hand.push(card2);
+                        let hand = &mut self.player_hands[self.hand_index];
+                        let card2 = hand.cards.pop().unwrap();
+                        let split_aces = matches!(card2.rank, Rank::Ace);
+                        hand.from_split = true;
+                        hand.split_aces = split_aces;
+                        let mut new_hand = Hand {
+                            from_split: true,
+                            split_aces,
+                            free_bet,
+                            ..Hand::new()
+                        };
+                        new_hand.cards.push(card2);
                         self.player_hands.push(new_hand);
+                        self.emit(GameEvent::HandSplit {
+                            hand_index: self.hand_index,
+                            new_hand_index: self.player_hands.len() - 1,
+                        });
                         self.state = GameState::Dealing;
                     }
                     PlayerAction::Surrender => {
-                        self.state = GameState::GameOver;
+                        if self.player_hands[self.hand_index].doubled {
+                            // the doubles are handed back and half the
+                            // remaining bet, the original stake, is lost
+                            self.bets[self.hand_index] = self.starting_bet * 2;
+                        }
+                        self.player_hands[self.hand_index].surrendered = true;
+                        self.state = match self.dealer_must_play_out() {
+                            true => GameState::DealerTurn,
+                            false => GameState::GameOver,
+                        };
+                    }
+                    PlayerAction::Switch => {
+                        panic!("Unreachable code: switching is decided before the player's turn")
                     }
                 }
             }
             GameState::DealerTurn => {
-                fn dealer_should_stand(game: &BlackjackState) -> bool {
-                    match game.dealer_hand_value(&game.dealer_hand, false) {
-                        Soft(n) if n >= 17 => game.rules.dealer_stands_on_all_17,
-                        Hard(n) if n >= 17 => true,
-                        Blackjack => true,
-                        _ => false,
-                    }
-                }
-                if dealer_should_stand(&self) {
+                if self.dealer_should_stand() {
                     self.state = GameState::GameOver;
                 } else {
                     // dealer hits
-                    {
-                        if self.dealer_hand.len() >= 2 && self.dealer_hand[1].face_down {
-                            self.dealer_hand[1].face_down = false;
-                        } else {
-                            let dealer_card = self.shoe.pop().unwrap();
-                            self.dealer_hand.push(dealer_card);
+                    // in Pontoon both dealer cards are face down, and both
+                    // are turned over before the round can end
+                    if self.dealer_hand.iter().any(|card| card.face_down) {
+                        for index in 0..self.dealer_hand.len() {
+                            if self.dealer_hand[index].face_down {
+                                self.dealer_hand[index].face_down = false;
+                                self.emit(GameEvent::HoleCardRevealed {
+                                    card: self.dealer_hand[index].clone(),
+                                });
+                            }
                         }
+                    } else {
+                        let dealer_card = self.draw()?;
+                        self.dealer_hand.push(dealer_card);
+                        self.emit_card_dealt(CardRecipient::Dealer);
                     };
                     self.state = {
-                        let all_blackjacks = self.player_hands.iter().all(|hand| {
-                            let aces_were_split = self.player_split_aces(&self.player_hands);
-                            let player_hand_value = self.player_hand_value(hand, aces_were_split);
-                            matches!(player_hand_value, Blackjack)
-                        });
-                        if all_blackjacks {
+                        let all_blackjacks = self
+                            .player_hands
+                            .iter()
+                            .all(|hand| matches!(self.player_hand_value(hand), Blackjack));
+                        if all_blackjacks && !self.dealer_must_play_out() {
                             // dealer has now revealed face down card and is up against all blackjacks
                             // no need to play out the hand
                             GameState::GameOver
-                        } else if dealer_should_stand(&self) {
+                        } else if self.dealer_should_stand() {
                             GameState::GameOver
                         } else {
                             GameState::DealerTurn
@@ -654,27 +1230,29 @@ impl BlackjackState {
                 panic!("Game is over; no more actions allowed.");
             }
         }
+        Ok(())
     }
 
     pub fn player_hand_outcomes(&self) -> Vec<HandOutcome> {
         if !matches!(&self.state, GameState::GameOver) {
             panic!("Game is not over; cannot determine outcomes.");
         }
-        // check for surrender
-        if self.player_hands.len() == 1
-            && self.player_hands[0].len() == 2
-            && self.dealer_hand[1].face_down
-        {
-            return vec![HandOutcome::Surrendered];
-        }
-
         self.player_hands
             .iter()
             .map(|hand| {
-                let player_hand_value =
-                    self.player_hand_value(hand, self.player_split_aces(&self.player_hands));
+                if hand.surrendered {
+                    return HandOutcome::Surrendered;
+                }
+                if self.is_pontoon() {
+                    return self.pontoon_outcome(hand);
+                }
+                let player_hand_value = self.player_hand_value(hand);
                 let dealer_hand_value = self.dealer_hand_value(&self.dealer_hand, false);
+                let spanish21 = matches!(self.rules.variant, GameVariant::Spanish21);
+                let ties_lose = matches!(self.rules.variant, GameVariant::DoubleExposure);
                 match (player_hand_value, dealer_hand_value) {
+                    // a player blackjack always wins in Spanish 21
+                    (Blackjack, Blackjack) if spanish21 => HandOutcome::Won(WinReason::Blackjack),
                     (Blackjack, Blackjack) => HandOutcome::Push,
                     (Blackjack, _) => HandOutcome::Won(WinReason::Blackjack),
                     (_, Blackjack) => HandOutcome::Lost(LossReason::DealerBlackjack),
@@ -688,14 +1266,32 @@ impl BlackjackState {
                         }
                         let player_number = to_number(&player_hand_value);
                         let dealer_number = to_number(&dealer_hand_value);
+                        let bonus = match spanish21 && !hand.doubled {
+                            true => BonusHand::find(&hand.cards),
+                            false => None,
+                        };
                         if player_number > 21 {
                             HandOutcome::Lost(LossReason::Bust)
+                        } else if let Some(bonus) = bonus {
+                            HandOutcome::Won(WinReason::Bonus(bonus))
+                        } else if self
+                            .rules
+                            .charlie
+                            .is_some_and(|cards| hand.cards.len() >= cards as usize)
+                        {
+                            HandOutcome::Won(WinReason::Charlie)
+                        } else if self.dealer_pushes_on_22() {
+                            HandOutcome::Push
                         } else if dealer_number > 21 {
                             HandOutcome::Won(WinReason::DealerBust)
                         } else if player_number > dealer_number {
                             HandOutcome::Won(WinReason::HigherHand)
+                        } else if spanish21 && player_number == 21 {
+                            HandOutcome::Won(WinReason::TwentyOne)
                         } else if player_number < dealer_number {
                             HandOutcome::Lost(LossReason::LowerHand)
+                        } else if ties_lose {
+                            HandOutcome::Lost(LossReason::Tie)
                         } else {
                             HandOutcome::Push
                         }
@@ -705,3 +1301,213 @@ impl BlackjackState {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackjack::{
+        presets::{find_preset, PRESETS},
+        scenario::play_to_player_turn,
+    };
+    use crate::monte_carlo::{play_optimal_round, round_net};
+
+    #[test]
+    fn test_invalid_ruleset_is_rejected() {
+        let rules = BlackjackRuleset {
+            double_after_split: false,
+            double_on_split_ace: true,
+            ..PRESETS[0].rules
+        };
+        let problems = init_state(Money::from_dollars(1), rules).unwrap_err();
+        assert_eq!(
+            problems,
+            vec![RulesetProblem::DoubleOnSplitAceWithoutDoubleAfterSplit]
+        );
+        assert!(init_state(Money::from_dollars(1), rules.normalize()).is_ok());
+    }
+
+    #[test]
+    fn test_split_hands_follow_double_after_split() {
+        let mut rules = PRESETS[0].rules;
+        rules.double_after_split = false;
+        let mut game = play_to_player_turn("8s 8h / 6d 7c / 3c 2d", rules);
+        game.next_state(Some(PlayerAction::Split));
+        game.next_state(None);
+        let hand = &game.player_hands[0];
+        assert!(hand.from_split && !hand.split_aces && !hand.doubled);
+        assert!(!game.allowed_actions().contains(&PlayerAction::DoubleDown));
+
+        rules.double_after_split = true;
+        let mut game = play_to_player_turn("8s 8h / 6d 7c / 3c 2d", rules);
+        game.next_state(Some(PlayerAction::Split));
+        game.next_state(None);
+        game.next_state(Some(PlayerAction::DoubleDown));
+        assert!(game.player_hands[0].doubled);
+        assert!(!game.player_hands[1].doubled);
+    }
+
+    #[test]
+    fn test_split_unlike_tens() {
+        let mut rules = PRESETS[0].rules;
+        let game = play_to_player_turn("Ks Qh / 6d 7c", rules);
+        assert!(!game.allowed_actions().contains(&PlayerAction::Split));
+
+        rules.split_unlike_tens = true;
+        let game = play_to_player_turn("Ks Qh / 6d 7c", rules);
+        assert!(game.allowed_actions().contains(&PlayerAction::Split));
+        // the strategy still stands on 20
+        assert_eq!(game.get_optimal_move(), PlayerAction::Stand);
+    }
+
+    #[test]
+    fn test_pair_splits_limit_resplitting() {
+        let mut rules = PRESETS[0].rules;
+        rules.pair_splits = ruleset::PairSplits::ANY.with_max_splits(8, 1);
+        let mut game = play_to_player_turn("8s 8h / 6d 7c / 8c 2d 3c 3h", rules);
+        game.next_state(Some(PlayerAction::Split));
+        game.next_state(None);
+        // 8-8 again, but 8s can't be resplit
+        assert!(!game.allowed_actions().contains(&PlayerAction::Split));
+        assert_eq!(game.get_optimal_move(), PlayerAction::Stand);
+
+        // other pairs can still be resplit
+        let mut game = play_to_player_turn("3s 3h / 6d 7c / 3c", rules);
+        game.next_state(Some(PlayerAction::Split));
+        game.next_state(None);
+        assert!(game.allowed_actions().contains(&PlayerAction::Split));
+    }
+
+    #[test]
+    fn test_double_for_less() {
+        let mut rules = PRESETS[0].rules;
+        let half = PlayerAction::DoubleFor(Money::from_cents(50));
+        let game = play_to_player_turn("6s 5h / 6d 7c / Tc", rules);
+        assert_eq!(game.max_double_for(), None);
+        assert!(!game.is_action_allowed(half));
+
+        rules.double_for_less = true;
+        let mut game = play_to_player_turn("6s 5h / 6d 7c / Tc Td", rules);
+        assert_eq!(game.max_double_for(), Some(Money::from_dollars(1)));
+        assert!(game.is_action_allowed(half));
+        assert!(!game.is_action_allowed(PlayerAction::DoubleFor(Money::from_dollars(2))));
+        game.next_state(Some(half));
+        play_optimal_round(&mut game);
+        assert!(game.player_hands[0].doubled);
+        assert_eq!(game.bets, vec![Money::from_cents(150)]);
+        assert_eq!(round_net(&game), Money::from_cents(150));
+    }
+
+    #[test]
+    fn test_multi_card_double() {
+        let mut rules = PRESETS[0].rules;
+        let mut game = play_to_player_turn("2s 3h / 6d 7c / 5c Td", rules);
+        game.next_state(Some(PlayerAction::Hit));
+        assert!(!game.allowed_actions().contains(&PlayerAction::DoubleDown));
+
+        rules.multi_card_double = Some(3);
+        let mut game = play_to_player_turn("2s 3h / 6d 7c / 5c Td", rules);
+        game.next_state(Some(PlayerAction::Hit));
+        // 2-3-5 is a hard 10 against a 6
+        assert_eq!(game.get_optimal_move(), PlayerAction::DoubleDown);
+        game.next_state(Some(PlayerAction::DoubleDown));
+        assert_eq!(game.player_hands[0].cards.len(), 4);
+        assert_eq!(game.bets, vec![Money::from_dollars(2)]);
+    }
+
+    #[test]
+    fn test_dealer_policy() {
+        let mut rules = PRESETS[1].rules;
+        let policy = ruleset::DealerPolicy {
+            stands_on_soft: 19,
+            push_22: true,
+            ..ruleset::DealerPolicy::STANDS_ON_SOFT_17
+        };
+        rules.dealer_policy = Some(policy);
+        // hits soft 18 and goes on to a 22, which pushes
+        let mut game = play_to_player_turn("Ts 9h / Ad 7c / 4c Tc", rules);
+        game.next_state(Some(PlayerAction::Stand));
+        play_optimal_round(&mut game);
+        assert_eq!(game.dealer_hand.len(), 4);
+        assert_eq!(game.player_hand_outcomes(), vec![HandOutcome::Push]);
+
+        rules.dealer_policy = Some(ruleset::DealerPolicy {
+            stands_on_hard: 16,
+            ..policy
+        });
+        let mut game = play_to_player_turn("Ts 9h / Td 6c / 4c", rules);
+        game.next_state(Some(PlayerAction::Stand));
+        play_optimal_round(&mut game);
+        assert_eq!(game.dealer_hand.len(), 2);
+        assert_eq!(
+            game.player_hand_outcomes(),
+            vec![HandOutcome::Won(WinReason::HigherHand)]
+        );
+    }
+
+    #[test]
+    fn test_peek_policy() {
+        let mut rules = PRESETS[1].rules;
+        rules.dealer_peeks = ruleset::PeekPolicy::AceOnly;
+        // no peek under a ten: no surrender, and 11 is hit rather than doubled
+        let game = play_to_player_turn("6s 5h / Td 7c", rules);
+        assert!(!game.dealer_peeked());
+        assert!(!game.allowed_actions().contains(&PlayerAction::Surrender));
+        assert_eq!(game.get_optimal_move(), PlayerAction::Hit);
+        let mut game = play_to_player_turn("6s 5h / Td Ac / 2c", rules);
+        assert!(matches!(game.state, GameState::PlayerTurn));
+        game.next_state(Some(PlayerAction::DoubleDown));
+        play_optimal_round(&mut game);
+        assert_eq!(round_net(&game), Money::from_dollars(-2));
+
+        // the dealer still peeks under an ace
+        let game = play_to_player_turn("6s 5h / Ad Kc", rules);
+        assert!(game.dealer_peeked());
+        assert!(matches!(game.state, GameState::DealerTurn));
+    }
+
+    #[test]
+    fn test_surrender_is_recorded_on_the_hand() {
+        let mut game = play_to_player_turn("Ts 6h / Ac 7d", PRESETS[0].rules);
+        game.next_state(Some(PlayerAction::Surrender));
+        assert!(game.player_hands[0].surrendered);
+        assert_eq!(game.player_hand_outcomes(), vec![HandOutcome::Surrendered]);
+    }
+
+    fn card(rank: Rank) -> Card {
+        Card {
+            suit: Suit::Spades,
+            rank,
+            face_down: false,
+        }
+    }
+
+    #[test]
+    fn test_dealer_hitting_soft_17_stands_on_soft_18() {
+        let rules = find_preset("downtown-vegas").unwrap().rules;
+        assert!(!rules.dealer_stands_on_all_17);
+        // the player is dealt 10, 9 and the dealer A, 7, with fives to draw
+        let shoe = [
+            Rank::Five,
+            Rank::Five,
+            Rank::Seven,
+            Rank::Nine,
+            Rank::Ace,
+            Rank::Ten,
+        ]
+        .into_iter()
+        .map(card)
+        .collect();
+        let mut game = init_state_with_shoe(Money::from_dollars(1), rules, shoe).unwrap();
+        while !matches!(game.state, GameState::GameOver) {
+            match game.state {
+                GameState::PlayerTurn => game.next_state(Some(PlayerAction::Stand)),
+                _ => game.next_state(None),
+            }
+        }
+        assert_eq!(game.dealer_hand.len(), 2);
+        assert!(matches!(
+            game.dealer_hand_value(&game.dealer_hand, false),
+            Soft(18)
+        ));
+    }
+}
@@ -0,0 +1,69 @@
+// Charlie rules: a player hand that reaches a set number of cards without
+// busting wins outright. One card short of a Charlie, hitting wins unless the
+// card busts the hand, which makes hitting worth it on many hands the
+// strategy tables would stand on.
+use crate::blackjack::{
+    constants::basic_strategy_tables::Strategy, hand_ev::PlayedHand, BlackjackState, HandValue,
+};
+
+impl BlackjackState {
+    // Whether the current hand is one card short of a Charlie and hitting it
+    // is worth more than standing
+    pub(super) fn charlie_hit_beats_stand(&self) -> bool {
+        let hand = &self.player_hands[self.hand_index];
+        if self.rules.charlie != Some(hand.cards.len() as u8 + 1) {
+            return false;
+        }
+        let total = match self.player_hand_value(hand) {
+            HandValue::Hard(n) => n,
+            HandValue::Soft(_) => return true, // can't bust
+            HandValue::Blackjack => return false,
+        };
+        // the hand stands unless it's worth hitting
+        let played = PlayedHand::new(&self.rules, self.dealer_outcomes(), &|_, _| &Strategy::S);
+        2.0 * played.no_bust(total) - 1.0 > played.stand_ev(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blackjack::{
+        money::Money, presets::find_preset, ruleset::BlackjackRuleset, scenario, HandOutcome,
+        PlayerAction, WinReason,
+    };
+    use crate::monte_carlo::{play_optimal_round, round_net};
+
+    fn charlie_rules(charlie: u8) -> BlackjackRuleset {
+        let mut rules = find_preset("vegas-strip").unwrap().rules;
+        rules.charlie = Some(charlie);
+        rules
+    }
+
+    #[test]
+    fn test_charlie_wins_against_any_total() {
+        let mut game = scenario::play_to_player_turn("2c 3d / Th Ks / 2h 2s 3c", charlie_rules(5));
+        for _ in 0..3 {
+            game.next_state(Some(PlayerAction::Hit));
+        }
+        play_optimal_round(&mut game);
+        assert_eq!(
+            game.player_hand_outcomes(),
+            vec![HandOutcome::Won(WinReason::Charlie)]
+        );
+        assert_eq!(round_net(&game), Money::from_dollars(1));
+    }
+
+    #[test]
+    fn test_hits_a_stiff_hand_one_card_short() {
+        let mut game = scenario::play_to_player_turn("2c 3d / 2h 7s / 4h 4s", charlie_rules(5));
+        game.next_state(Some(PlayerAction::Hit));
+        game.next_state(Some(PlayerAction::Hit));
+        // a four card 13 against a 2 usually stands
+        assert_eq!(game.get_optimal_move(), PlayerAction::Hit);
+
+        let mut game = scenario::play_to_player_turn("2c 3d / 2h 7s / 4h 4s", charlie_rules(6));
+        game.next_state(Some(PlayerAction::Hit));
+        game.next_state(Some(PlayerAction::Hit));
+        assert_eq!(game.get_optimal_move(), PlayerAction::Stand);
+    }
+}
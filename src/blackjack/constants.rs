@@ -59,6 +59,32 @@ pub const UNSHUFFLED_DECK: [Card; 52] = [
   Card { suit: Suit::Spades, rank: Rank::Ace, face_down: false, },
 ];
 
+// Spanish 21 decks have the four tens taken out; jacks, queens and kings stay
+pub const SPANISH_DECK: [Card; 48] = without_tens(&UNSHUFFLED_DECK);
+
+const fn without_tens(deck: &[Card; 52]) -> [Card; 48] {
+    let mut cards = [const {
+        Card {
+            suit: Suit::Hearts,
+            rank: Rank::Two,
+            face_down: false,
+        }
+    }; 48];
+    let (mut i, mut j) = (0, 0);
+    while i < deck.len() {
+        if !matches!(deck[i].rank, Rank::Ten) {
+            cards[j] = Card {
+                suit: deck[i].suit,
+                rank: deck[i].rank,
+                face_down: false,
+            };
+            j += 1;
+        }
+        i += 1;
+    }
+    cards
+}
+
 pub mod basic_strategy_tables {
     pub enum Strategy {
         H,  // hit
@@ -69,6 +95,13 @@ pub mod basic_strategy_tables {
         PH, // split if double down after split is possible, otherwise hit
         RH, // surrender if possible, otherwise hit
     }
+    // hard, soft and pair tables, with a column for each dealer up card (or
+    // dealer total)
+    pub type StrategyTables<'a, const N: usize> = (
+        &'a [[Strategy; N]; 13],
+        &'a [[Strategy; N]; 9],
+        &'a [[Strategy; N]; 10],
+    );
     use Strategy::*;
     pub const HARD: [[Strategy; 10]; 13] = [
         /*
@@ -114,4 +147,256 @@ pub mod basic_strategy_tables {
         [S, S, S, S, S, S, S, S, S, S],   // 10
         [P, P, P, P, P, P, P, P, P, P],   // Ace
     ];
+
+    // Spanish 21 without tens in the deck: the player doubles and splits
+    // less and hits stiff hands more often
+    pub mod spanish21 {
+        use super::Strategy::{self, *};
+        pub const HARD: [[Strategy; 10]; 13] = [
+            /*
+            |2  3  4  5  6  7  8  9  10 A */
+            [H, H, H, H, H, H, H, H, H, H],  // 5-8
+            [H, H, H, H, D, H, H, H, H, H],  // 9
+            [D, D, D, D, D, D, D, H, H, H],  // 10
+            [D, D, D, D, D, D, D, D, D, D],  // 11
+            [H, H, S, S, S, H, H, H, H, H],  // 12
+            [H, H, S, S, S, H, H, H, H, H],  // 13
+            [H, H, S, S, S, H, H, H, H, H],  // 14
+            [S, S, S, S, S, H, H, H, H, H],  // 15
+            [S, S, S, S, S, H, H, H, H, RH], // 16
+            [S, S, S, S, S, S, S, S, S, S],  // 17
+            [S, S, S, S, S, S, S, S, S, S],  // 18
+            [S, S, S, S, S, S, S, S, S, S],  // 19
+            [S, S, S, S, S, S, S, S, S, S],  // 20
+        ];
+        pub const SOFT: [[Strategy; 10]; 9] = [
+            /*
+            |2  3  4  5  6  7  8  9  10 A */
+            [H, H, H, H, H, H, H, H, H, H],    // 12
+            [H, H, H, D, D, H, H, H, H, H],    // 13
+            [H, H, H, D, D, H, H, H, H, H],    // 14
+            [H, H, H, D, D, H, H, H, H, H],    // 15
+            [H, H, H, D, D, H, H, H, H, H],    // 16
+            [H, H, D, D, D, H, H, H, H, H],    // 17
+            [S, S, DS, DS, DS, S, S, H, H, H], // 18
+            [S, S, S, S, S, S, S, S, S, S],    // 19
+            [S, S, S, S, S, S, S, S, S, S],    // 20
+        ];
+        pub const SPLIT: [[Strategy; 10]; 10] = [
+            /*
+            |2  3  4  5  6  7  8  9  10 A */
+            [H, P, P, P, P, P, P, H, H, H], // 2
+            [P, P, P, P, P, P, P, H, H, H], // 3
+            [H, H, H, H, H, H, H, H, H, H], // 4
+            [D, D, D, D, D, D, D, H, H, H], // 5
+            [H, H, P, P, P, H, H, H, H, H], // 6
+            [P, P, P, P, P, P, H, H, H, H], // 7
+            [P, P, P, P, P, P, P, P, P, P], // 8
+            [S, P, P, P, P, S, P, P, S, S], // 9
+            [S, S, S, S, S, S, S, S, S, S], // 10
+            [P, P, P, P, P, P, P, P, P, P], // Ace
+        ];
+    }
+
+    // Free Bet Blackjack: free doubles on 9-11 and free splits are always
+    // taken, while a dealer 22 pushing makes paid soft doubles worth less
+    pub mod free_bet {
+        use super::Strategy::{self, *};
+        pub const HARD: [[Strategy; 10]; 13] = [
+            /*
+            |2  3  4  5  6  7  8  9  10 A */
+            [H, H, H, H, H, H, H, H, H, H], // 5-8
+            [D, D, D, D, D, D, D, D, D, D], // 9
+            [D, D, D, D, D, D, D, D, D, D], // 10
+            [D, D, D, D, D, D, D, D, D, D], // 11
+            [H, H, H, S, S, H, H, H, H, H], // 12
+            [H, S, S, S, S, H, H, H, H, H], // 13
+            [S, S, S, S, S, H, H, H, H, H], // 14
+            [S, S, S, S, S, H, H, H, H, H], // 15
+            [S, S, S, S, S, H, H, H, H, H], // 16
+            [S, S, S, S, S, S, S, S, S, S], // 17
+            [S, S, S, S, S, S, S, S, S, S], // 18
+            [S, S, S, S, S, S, S, S, S, S], // 19
+            [S, S, S, S, S, S, S, S, S, S], // 20
+        ];
+        pub const SOFT: [[Strategy; 10]; 9] = [
+            /*
+            |2  3  4  5  6  7  8  9  10 A */
+            [H, H, H, H, H, H, H, H, H, H],   // 12
+            [H, H, H, H, H, H, H, H, H, H],   // 13
+            [H, H, H, H, H, H, H, H, H, H],   // 14
+            [H, H, H, H, H, H, H, H, H, H],   // 15
+            [H, H, H, H, H, H, H, H, H, H],   // 16
+            [H, H, H, H, D, H, H, H, H, H],   // 17
+            [S, S, S, DS, DS, S, S, H, H, H], // 18
+            [S, S, S, S, S, S, S, S, S, S],   // 19
+            [S, S, S, S, S, S, S, S, S, S],   // 20
+        ];
+        pub const SPLIT: [[Strategy; 10]; 10] = [
+            /*
+            |2  3  4  5  6  7  8  9  10 A */
+            [P, P, P, P, P, P, P, P, P, P], // 2
+            [P, P, P, P, P, P, P, P, P, P], // 3
+            [P, P, P, P, P, P, P, P, P, P], // 4
+            [D, D, D, D, D, D, D, D, D, D], // 5
+            [P, P, P, P, P, P, P, P, P, P], // 6
+            [P, P, P, P, P, P, P, P, P, P], // 7
+            [P, P, P, P, P, P, P, P, P, P], // 8
+            [P, P, P, P, P, P, P, P, P, P], // 9
+            [S, S, S, S, S, S, S, S, S, S], // 10
+            [P, P, P, P, P, P, P, P, P, P], // Ace
+        ];
+    }
+
+    // Blackjack Switch: a dealer 22 pushing makes standing on stiff hands
+    // and doubling worth less, as does splitting against a high up card
+    pub mod switch {
+        use super::Strategy::{self, *};
+        pub const HARD: [[Strategy; 10]; 13] = [
+            /*
+            |2  3  4  5  6  7  8  9  10 A */
+            [H, H, H, H, H, H, H, H, H, H], // 5-8
+            [H, H, H, H, D, H, H, H, H, H], // 9
+            [D, D, D, D, D, D, D, H, H, H], // 10
+            [D, D, D, D, D, D, D, D, D, H], // 11
+            [H, H, H, H, S, H, H, H, H, H], // 12
+            [H, H, S, S, S, H, H, H, H, H], // 13
+            [S, S, S, S, S, H, H, H, H, H], // 14
+            [S, S, S, S, S, H, H, H, H, H], // 15
+            [S, S, S, S, S, H, H, H, H, H], // 16
+            [S, S, S, S, S, S, S, S, S, S], // 17
+            [S, S, S, S, S, S, S, S, S, S], // 18
+            [S, S, S, S, S, S, S, S, S, S], // 19
+            [S, S, S, S, S, S, S, S, S, S], // 20
+        ];
+        pub const SOFT: [[Strategy; 10]; 9] = [
+            /*
+            |2  3  4  5  6  7  8  9  10 A */
+            [H, H, H, H, H, H, H, H, H, H],   // 12
+            [H, H, H, H, H, H, H, H, H, H],   // 13
+            [H, H, H, H, H, H, H, H, H, H],   // 14
+            [H, H, H, H, H, H, H, H, H, H],   // 15
+            [H, H, H, H, H, H, H, H, H, H],   // 16
+            [H, H, H, H, D, H, H, H, H, H],   // 17
+            [S, S, S, DS, DS, S, S, H, H, H], // 18
+            [S, S, S, S, S, S, S, S, S, S],   // 19
+            [S, S, S, S, S, S, S, S, S, S],   // 20
+        ];
+        pub const SPLIT: [[Strategy; 10]; 10] = [
+            /*
+            |2  3  4  5  6  7  8  9  10 A */
+            [H, H, H, H, P, P, H, H, H, H], // 2
+            [H, H, H, P, P, P, H, H, H, H], // 3
+            [H, H, H, H, H, H, H, H, H, H], // 4
+            [D, D, D, D, D, D, D, H, H, H], // 5
+            [H, H, P, P, P, H, H, H, H, H], // 6
+            [P, P, P, P, P, P, H, H, H, H], // 7
+            [P, P, P, P, P, P, P, H, H, H], // 8
+            [S, S, P, P, P, S, P, P, S, S], // 9
+            [S, S, S, S, S, S, S, S, S, S], // 10
+            [P, P, P, P, P, P, P, P, P, P], // Ace
+        ];
+    }
+
+    // Double Exposure, looked up by the dealer's total: hard 4 to 16 then
+    // soft 12 to 17. Ties losing makes standing on a stiff hand against a
+    // dealer 7 to 11 worse, and against a dealer who is done drawing the
+    // player simply hits until beating the dealer.
+    pub mod double_exposure {
+        use super::Strategy::{self, *};
+        pub const HARD: [[Strategy; 19]; 13] = [
+            /*
+            |4  5  6  7  8  9  10 11 12 13 14 15 16 S12 S13 S14 S15 S16 S17 */
+            [H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H], // 5-8
+            [D, D, D, H, H, H, H, H, D, D, D, D, D, H, H, H, H, H, H], // 9
+            [D, D, D, D, D, H, H, H, D, D, D, D, D, H, H, D, D, D, H], // 10
+            [D, D, D, D, D, D, H, H, D, D, D, D, D, D, D, D, D, D, D], // 11
+            [S, S, S, H, H, H, H, H, S, S, S, S, S, H, H, S, S, S, H], // 12
+            [S, S, S, H, H, H, H, H, S, S, S, S, S, S, S, S, S, S, H], // 13
+            [S, S, S, H, H, H, H, S, S, S, S, S, S, S, S, S, S, S, H], // 14
+            [S, S, S, H, H, H, S, S, S, S, S, S, S, S, S, S, S, S, H], // 15
+            [S, S, S, H, S, S, S, S, S, S, S, S, S, S, S, S, S, S, H], // 16
+            [S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, H], // 17
+            [S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S], // 18
+            [S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S], // 19
+            [S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S], // 20
+        ];
+        pub const SOFT: [[Strategy; 19]; 9] = [
+            /*
+            |4  5  6  7  8  9  10 11 12 13 14 15 16 S12 S13 S14 S15 S16 S17 */
+            [H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H], // 12
+            [H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H], // 13
+            [H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H], // 14
+            [H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H], // 15
+            [H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H], // 16
+            [H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H, H], // 17
+            [H, H, H, S, H, H, H, H, S, S, S, S, S, H, H, H, H, H, S], // 18
+            [S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S], // 19
+            [S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S], // 20
+        ];
+        pub const SPLIT: [[Strategy; 19]; 10] = [
+            /*
+            |4  5  6  7  8  9  10 11 12 13 14 15 16 S12 S13 S14 S15 S16 S17 */
+            [P, P, P, H, H, H, H, H, P, P, P, P, P, H, H, H, H, H, H], // 2
+            [P, P, P, H, H, H, H, H, P, P, P, P, P, H, H, H, H, H, H], // 3
+            [H, H, P, H, H, H, H, H, P, P, P, P, P, H, H, H, H, H, H], // 4
+            [D, D, D, D, D, H, H, H, D, D, D, D, D, H, H, D, D, D, H], // 5
+            [P, P, P, H, H, H, H, H, P, P, P, P, P, H, H, S, S, S, H], // 6
+            [P, P, P, H, H, H, S, S, P, P, P, P, P, S, S, S, S, S, H], // 7
+            [P, P, P, P, P, S, S, S, P, P, P, P, P, S, S, S, S, P, P], // 8
+            [P, P, P, S, P, S, S, S, P, P, P, P, P, S, S, S, P, P, S], // 9
+            [S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S], // 10
+            [P, P, P, P, P, P, P, H, P, P, P, P, P, P, P, P, P, P, P], // Ace
+        ];
+    }
+
+    // Pontoon, where no dealer card is visible: a column for each number of
+    // cards in the hand instead, D buying a card. Hands under 15 can't stick,
+    // and with four cards any fifth card that doesn't bust makes a five-card
+    // trick, so four card hands twist up to hard 17.
+    pub mod pontoon {
+        use super::Strategy::{self, *};
+        pub const HARD: [[Strategy; 3]; 13] = [
+            /*
+            |2  3  4 cards */
+            [D, D, H], // 5-8
+            [D, D, H], // 9
+            [D, D, H], // 10
+            [D, D, H], // 11
+            [H, H, H], // 12
+            [H, H, H], // 13
+            [H, H, H], // 14
+            [S, S, H], // 15
+            [S, S, H], // 16
+            [S, S, H], // 17
+            [S, S, S], // 18
+            [S, S, S], // 19
+            [S, S, S], // 20
+        ];
+        pub const SOFT: [[Strategy; 3]; 9] = [
+            /*
+            |2  3  4 cards */
+            [D, D, H], // 12
+            [D, D, H], // 13
+            [D, D, H], // 14
+            [D, D, H], // 15
+            [D, D, H], // 16
+            [D, D, H], // 17
+            [D, D, H], // 18
+            [S, D, H], // 19
+            [S, D, H], // 20
+        ];
+        pub const SPLIT: [Strategy; 10] = [
+            D, // 2
+            D, // 3
+            D, // 4
+            D, // 5
+            H, // 6
+            H, // 7
+            P, // 8
+            S, // 9
+            S, // 10
+            D, // Ace
+        ];
+    }
 }
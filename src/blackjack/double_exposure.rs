@@ -0,0 +1,78 @@
+// Double Exposure: both dealer cards are dealt face up, so the player knows
+// the dealer's total before playing. To pay for it, the dealer wins ties
+// (except a blackjack against a blackjack, which pushes) and a blackjack pays
+// even money (set by the ruleset).
+use crate::blackjack::{
+    constants::basic_strategy_tables::{double_exposure, Strategy},
+    ruleset::GameVariant,
+    BlackjackState, HandValue,
+};
+
+impl BlackjackState {
+    // Whether the dealer's second card is dealt face up
+    pub fn hole_card_exposed(&self) -> bool {
+        matches!(self.rules.variant, GameVariant::DoubleExposure)
+    }
+
+    pub(super) fn double_exposure_strategy(&self, can_split: bool) -> &'static Strategy {
+        let total = |value: HandValue| match value {
+            HandValue::Hard(n) | HandValue::Soft(n) => n,
+            HandValue::Blackjack => panic!("Unreachable code."),
+        };
+        let dealer_value = self.dealer_hand_value(&self.dealer_hand, false);
+        if self.dealer_should_stand() {
+            // ties lose, so keep hitting until the hand beats the dealer
+            let player_value = self.player_hand_value(&self.player_hands[self.hand_index]);
+            return match total(player_value) > total(dealer_value) {
+                true => &Strategy::S,
+                false => &Strategy::H,
+            };
+        }
+        // a dealer policy drawing to higher totals uses the highest column
+        let column = match dealer_value {
+            HandValue::Hard(n) => n.min(16) as usize - 4,
+            HandValue::Soft(n) => n.min(17) as usize - 12 + 13,
+            HandValue::Blackjack => panic!("Unreachable code."),
+        };
+        self.lookup_strategy(
+            (
+                &double_exposure::HARD,
+                &double_exposure::SOFT,
+                &double_exposure::SPLIT,
+            ),
+            column,
+            can_split,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blackjack::{scenario, HandOutcome, LossReason, PlayerAction};
+    use crate::monte_carlo::play_optimal_round;
+
+    #[test]
+    fn test_strategy_follows_dealer_total() {
+        // 12 stands against a dealer 16 but hits a dealer who has 17
+        let game = scenario::play_preset("double-exposure", "Tc 2d / Th 6s");
+        assert!(!game.dealer_hand[1].face_down);
+        assert_eq!(game.get_optimal_move(), PlayerAction::Stand);
+        let game = scenario::play_preset("double-exposure", "Tc 2d / 9h 8s");
+        assert_eq!(game.get_optimal_move(), PlayerAction::Hit);
+    }
+
+    #[test]
+    fn test_dealer_wins_ties_except_blackjack() {
+        let mut game = scenario::play_preset("double-exposure", "Th 8d / 9c 9s");
+        game.next_state(Some(PlayerAction::Stand));
+        play_optimal_round(&mut game);
+        assert_eq!(
+            game.player_hand_outcomes(),
+            vec![HandOutcome::Lost(LossReason::Tie)]
+        );
+
+        let mut game = scenario::play_preset("double-exposure", "As Kd / Ac Kh");
+        play_optimal_round(&mut game);
+        assert_eq!(game.player_hand_outcomes(), vec![HandOutcome::Push]);
+    }
+}
@@ -0,0 +1,192 @@
+use crate::blackjack::{
+    money::Money,
+    side_bets::{SideBetKind, SideBetOutcome},
+    Card, HandOutcome, HandValue, PlayerAction,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "kind")]
+pub enum CardRecipient {
+    Player { hand_index: usize },
+    Dealer,
+}
+
+// Emitted by `BlackjackState::next_state_with_events` for every transition.
+// A face down card is dealt without the card itself, which is only sent with
+// HoleCardRevealed.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind")]
+pub enum GameEvent {
+    CardDealt {
+        recipient: CardRecipient,
+        card: Option<Card>, // None while face down
+    },
+    HoleCardRevealed {
+        card: Card,
+    },
+    ActionTaken {
+        hand_index: usize,
+        action: PlayerAction,
+    },
+    HandSplit {
+        hand_index: usize,
+        new_hand_index: usize,
+    },
+    HandDoubled {
+        hand_index: usize,
+        bet: Money,
+    },
+    CardBought {
+        hand_index: usize,
+        bet: Money,
+    },
+    HandFinished {
+        hand_index: usize,
+        value: HandValue,
+    },
+    SideBetSettled {
+        bet: SideBetKind,
+        outcome: SideBetOutcome,
+    },
+    RoundSettled {
+        outcomes: Vec<HandOutcome>,
+    },
+}
+
+impl fmt::Display for GameEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameEvent::CardDealt {
+                recipient: CardRecipient::Player { hand_index },
+                card: Some(card),
+            } => write!(f, "Player hand {} is dealt {}", hand_index + 1, card),
+            GameEvent::CardDealt {
+                recipient: CardRecipient::Player { hand_index },
+                card: None,
+            } => write!(
+                f,
+                "Player hand {} is dealt a face down card",
+                hand_index + 1
+            ),
+            GameEvent::CardDealt {
+                recipient: CardRecipient::Dealer,
+                card: Some(card),
+            } => write!(f, "Dealer is dealt {}", card),
+            GameEvent::CardDealt {
+                recipient: CardRecipient::Dealer,
+                card: None,
+            } => f.write_str("Dealer is dealt a face down card"),
+            GameEvent::HoleCardRevealed { card } => write!(f, "Dealer reveals {}", card),
+            GameEvent::ActionTaken { hand_index, action } => {
+                write!(f, "Player hand {}: {:?}", hand_index + 1, action)
+            }
+            GameEvent::HandSplit {
+                hand_index,
+                new_hand_index,
+            } => write!(
+                f,
+                "Player hand {} is split into hand {}",
+                hand_index + 1,
+                new_hand_index + 1
+            ),
+            GameEvent::HandDoubled { hand_index, bet } => {
+                write!(f, "Player hand {} doubled to ${}", hand_index + 1, bet)
+            }
+            GameEvent::CardBought { hand_index, bet } => {
+                write!(
+                    f,
+                    "Player hand {} buys a card, bet now ${}",
+                    hand_index + 1,
+                    bet
+                )
+            }
+            GameEvent::HandFinished { hand_index, value } => {
+                write!(f, "Player hand {} finished with {}", hand_index + 1, value)
+            }
+            GameEvent::SideBetSettled {
+                bet,
+                outcome: SideBetOutcome::Won { hand, payout },
+            } => write!(f, "{} wins with {:?} ({})", bet, hand, payout),
+            GameEvent::SideBetSettled {
+                bet,
+                outcome: SideBetOutcome::Lost,
+            } => write!(f, "{} loses", bet),
+            GameEvent::RoundSettled { outcomes } => {
+                write!(f, "Round settled: {:?}", outcomes)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackjack::{init_state_with_shoe, presets::PRESETS, Card, GameState, Rank, Suit};
+
+    fn card(rank: Rank) -> Card {
+        Card {
+            suit: Suit::Spades,
+            rank,
+            face_down: false,
+        }
+    }
+
+    #[test]
+    fn test_round_emits_events_in_order() {
+        // cards are drawn from the end: player 10, dealer 9, player 8, dealer 7, then 5
+        let shoe = vec![
+            card(Rank::Five),
+            card(Rank::Seven),
+            card(Rank::Eight),
+            card(Rank::Nine),
+            card(Rank::Ten),
+        ];
+        let mut game =
+            init_state_with_shoe(Money::from_dollars(1), PRESETS[0].rules, shoe).unwrap();
+        let mut events = Vec::new();
+        while !matches!(game.state, GameState::GameOver) {
+            let action = match game.state {
+                GameState::PlayerTurn => Some(PlayerAction::Stand),
+                _ => None,
+            };
+            events.extend(game.next_state_with_events(action));
+        }
+        let dealt = events
+            .iter()
+            .filter(|event| matches!(event, GameEvent::CardDealt { .. }))
+            .count();
+        assert_eq!(dealt, 5);
+        assert!(matches!(
+            events[4],
+            GameEvent::ActionTaken {
+                hand_index: 0,
+                action: PlayerAction::Stand
+            }
+        ));
+        assert!(matches!(
+            events[5],
+            GameEvent::HandFinished {
+                hand_index: 0,
+                value: HandValue::Hard(18)
+            }
+        ));
+        // the hole card is only seen once revealed
+        assert!(matches!(
+            &events[3],
+            GameEvent::CardDealt {
+                recipient: CardRecipient::Dealer,
+                card: None
+            }
+        ));
+        assert!(matches!(
+            &events[6],
+            GameEvent::HoleCardRevealed { card } if card.rank == Rank::Seven
+        ));
+        assert!(matches!(
+            events.last(),
+            Some(GameEvent::RoundSettled { outcomes }) if outcomes.len() == 1
+        ));
+    }
+}
@@ -0,0 +1,84 @@
+// Free Bet Blackjack: the house pays for doubles on a hard 9, 10 or 11 and
+// for splitting any pair but tens. A free bet is paid if the hand wins but
+// costs the player nothing if it loses. In exchange, a dealer 22 pushes
+// every hand that isn't a blackjack or already busted.
+use crate::blackjack::{card_value, ruleset::GameVariant, BlackjackState, HandValue};
+
+impl BlackjackState {
+    fn free_bets(&self) -> bool {
+        matches!(self.rules.variant, GameVariant::FreeBet)
+    }
+
+    // Whether doubling the current hand would be paid for by the house
+    pub fn double_is_free(&self) -> bool {
+        let hand = &self.player_hands[self.hand_index];
+        self.free_bets()
+            && hand.cards.len() == 2
+            && !hand.doubled
+            && matches!(
+                self.player_hand_value(hand),
+                HandValue::Hard(9) | HandValue::Hard(10) | HandValue::Hard(11)
+            )
+    }
+
+    // Whether splitting the current hand would be paid for by the house
+    pub fn split_is_free(&self) -> bool {
+        let hand = &self.player_hands[self.hand_index];
+        self.free_bets() && hand.is_pair() && card_value(&hand.cards[0], false) != 10
+    }
+
+    // A dealer 22 pushes the hands that are still standing (also in
+    // Blackjack Switch, or by the ruleset's dealer policy)
+    pub(super) fn dealer_pushes_on_22(&self) -> bool {
+        self.rules.effective_dealer_policy().push_22
+            && matches!(
+                self.dealer_hand_value(&self.dealer_hand, false),
+                HandValue::Hard(22)
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blackjack::{money::Money, scenario, HandOutcome, PlayerAction};
+    use crate::monte_carlo::{play_optimal_round, round_net};
+
+    #[test]
+    fn test_free_split_and_double_cost_nothing_when_lost() {
+        // split eights for free, double the first hand (8-3) for free, the
+        // second hand (8-2) is played for the house's money
+        let mut game = scenario::play_preset("free-bet", "8s 8h / Kc 9d / 3c 5s 2d Kh");
+        assert!(game.split_is_free());
+        game.next_state(Some(PlayerAction::Split));
+        game.next_state(None);
+        assert!(game.double_is_free());
+        game.next_state(Some(PlayerAction::DoubleDown));
+        game.next_state(None);
+        game.next_state(Some(PlayerAction::Hit));
+        game.next_state(Some(PlayerAction::Stand));
+        play_optimal_round(&mut game);
+        // 8-3-5 = 16 and 8-2-K = 20 against 19
+        assert_eq!(
+            game.player_hands
+                .iter()
+                .map(|hand| hand.free_bet)
+                .collect::<Vec<_>>(),
+            vec![Money::from_dollars(1), Money::from_dollars(1)]
+        );
+        assert_eq!(round_net(&game), Money::from_dollars(0));
+    }
+
+    #[test]
+    fn test_dealer_22_pushes() {
+        let mut game = scenario::play_preset("free-bet", "Ks 9h / 6c Td / 6d");
+        game.next_state(Some(PlayerAction::Stand));
+        play_optimal_round(&mut game);
+        assert_eq!(game.player_hand_outcomes(), vec![HandOutcome::Push]);
+
+        let mut game = scenario::play_preset("free-bet", "Ks 5h / 6c Td / 9c 6d");
+        assert!(!game.split_is_free() && !game.double_is_free());
+        game.next_state(Some(PlayerAction::Hit));
+        play_optimal_round(&mut game);
+        assert_eq!(round_net(&game), Money::from_dollars(-1));
+    }
+}
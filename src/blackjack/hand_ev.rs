@@ -0,0 +1,287 @@
+// Expected returns drawing from an endless shoe, for the decisions the
+// strategy tables can't express (hitting to a Charlie, switching hands in
+// Blackjack Switch). The dealer's final total is worked out from the up card,
+// and a hand is played on by its table entries. Splits aren't played out, a
+// pair is played by its total.
+use crate::blackjack::{
+    card_value,
+    constants::basic_strategy_tables::Strategy,
+    ruleset::{BlackjackRuleset, DealerPolicy},
+    BlackjackState, Card, HandValue,
+};
+use std::collections::HashMap;
+
+// dealer outcomes by final total, 22 being a dealer 22 and 23 any higher bust
+pub(super) type DealerOutcomes = [f64; 24];
+
+// Chance of each card value (aces as 11) being drawn from `deck`
+pub(super) fn card_probabilities(deck: &[Card]) -> [f64; 12] {
+    let mut probabilities = [0f64; 12];
+    for card in deck {
+        probabilities[card_value(card, true) as usize] += 1.0 / deck.len() as f64;
+    }
+    probabilities
+}
+
+// Adds a card of `value` (aces as 11) to a total
+pub(super) fn draw(total: u8, soft: bool, value: u8) -> (u8, bool) {
+    let value = match value == 11 && total + 11 > 21 {
+        true => 1,
+        false => value,
+    };
+    let (total, soft) = (total + value, soft || value == 11);
+    match total > 21 && soft {
+        true => (total - 10, false),
+        false => (total, soft),
+    }
+}
+
+fn dealer_outcomes_from(
+    total: u8,
+    soft: bool,
+    probabilities: &[f64; 12],
+    policy: &DealerPolicy,
+    memo: &mut [[Option<DealerOutcomes>; 2]; 22],
+) -> DealerOutcomes {
+    let mut outcomes = [0f64; 24];
+    if total > 21 {
+        outcomes[total.min(23) as usize] = 1.0;
+        return outcomes;
+    }
+    let value = match soft {
+        true => HandValue::Soft(total),
+        false => HandValue::Hard(total),
+    };
+    if policy.stands_on(value) {
+        outcomes[total as usize] = 1.0;
+        return outcomes;
+    }
+    if let Some(outcomes) = memo[total as usize][soft as usize] {
+        return outcomes;
+    }
+    for value in 2..=11 {
+        let (total, soft) = draw(total, soft, value);
+        let next = dealer_outcomes_from(total, soft, probabilities, policy, memo);
+        for (outcome, chance) in outcomes.iter_mut().zip(next) {
+            *outcome += probabilities[value as usize] * chance;
+        }
+    }
+    memo[total as usize][soft as usize] = Some(outcomes);
+    outcomes
+}
+
+// Chance of each dealer outcome given the up card (aces as 11) and the hole
+// card, if it's known. A dealer who peeked doesn't have a blackjack.
+pub(super) fn dealer_outcomes(
+    rules: &BlackjackRuleset,
+    upcard: u8,
+    hole_card: Option<u8>,
+    peeked: bool,
+) -> DealerOutcomes {
+    let probabilities = card_probabilities(rules.variant.deck());
+    let policy = rules.effective_dealer_policy();
+    let mut memo = [[None; 2]; 22];
+    if let Some(hole_card) = hole_card {
+        let (total, soft) = draw(upcard, upcard == 11, hole_card);
+        return dealer_outcomes_from(total, soft, &probabilities, &policy, &mut memo);
+    }
+    let mut hole_card = probabilities;
+    if peeked {
+        match upcard {
+            11 => hole_card[10] = 0.0,
+            10 => hole_card[11] = 0.0,
+            _ => {}
+        }
+    }
+    let total_chance: f64 = hole_card.iter().sum();
+    let mut outcomes = [0f64; 24];
+    for value in 2..=11 {
+        let (total, soft) = draw(upcard, upcard == 11, value);
+        let next = dealer_outcomes_from(total, soft, &probabilities, &policy, &mut memo);
+        for (outcome, chance) in outcomes.iter_mut().zip(next) {
+            *outcome += hole_card[value as usize] / total_chance * chance;
+        }
+    }
+    outcomes
+}
+
+// What standing on `total` is worth
+pub(super) fn stand_ev(total: u8, outcomes: &DealerOutcomes, push_22: bool) -> f64 {
+    let dealer_22 = match push_22 {
+        true => 0.0,
+        false => outcomes[22],
+    };
+    outcomes[23]
+        + dealer_22
+        + (0..=21)
+            .map(|dealer_total| match total.cmp(&dealer_total) {
+                std::cmp::Ordering::Greater => outcomes[dealer_total as usize],
+                std::cmp::Ordering::Less => -outcomes[dealer_total as usize],
+                std::cmp::Ordering::Equal => 0.0,
+            })
+            .sum::<f64>()
+}
+
+// A hand played on by `strategy`, which gives the table entry for a total
+// and whether it's soft
+pub(super) struct PlayedHand<'a> {
+    outcomes: DealerOutcomes,
+    probabilities: [f64; 12],
+    push_22: bool,
+    surrender: bool,
+    charlie: Option<u8>,
+    strategy: &'a dyn Fn(u8, bool) -> &'a Strategy,
+    memo: HashMap<(u8, bool, u8), f64>,
+}
+impl<'a> PlayedHand<'a> {
+    pub(super) fn new(
+        rules: &BlackjackRuleset,
+        outcomes: DealerOutcomes,
+        strategy: &'a dyn Fn(u8, bool) -> &'a Strategy,
+    ) -> PlayedHand<'a> {
+        PlayedHand {
+            outcomes,
+            probabilities: card_probabilities(rules.variant.deck()),
+            push_22: rules.variant.dealer_22_pushes() || rules.effective_dealer_policy().push_22,
+            surrender: rules.surrender,
+            charlie: rules.charlie,
+            strategy,
+            memo: HashMap::new(),
+        }
+    }
+
+    pub(super) fn stand_ev(&self, total: u8) -> f64 {
+        stand_ev(total, &self.outcomes, self.push_22)
+    }
+
+    // Chance that a card doesn't bust a hard `total`
+    pub(super) fn no_bust(&self, total: u8) -> f64 {
+        (2..=11)
+            .filter(|&value| total + if value == 11 { 1 } else { value } <= 21)
+            .map(|value| self.probabilities[value as usize])
+            .sum()
+    }
+
+    // What a hand of `total` and `cards` cards is worth once a card is added
+    // and nothing more is done with it
+    fn ev_after_one_card(&self, total: u8, soft: bool, cards: u8) -> f64 {
+        (2..=11)
+            .map(|value| {
+                let (total, _) = draw(total, soft, value);
+                self.probabilities[value as usize]
+                    * match (total > 21, self.charlie == Some(cards + 1)) {
+                        (true, _) => -1.0,
+                        (false, true) => 1.0,
+                        (false, false) => self.stand_ev(total),
+                    }
+            })
+            .sum()
+    }
+
+    fn hit_ev(&mut self, total: u8, soft: bool, cards: u8) -> f64 {
+        (2..=11)
+            .map(|value| {
+                let (total, soft) = draw(total, soft, value);
+                self.probabilities[value as usize] * self.ev(total, soft, cards + 1)
+            })
+            .sum()
+    }
+
+    // What a hand of `total` and `cards` cards is worth played on from here
+    pub(super) fn ev(&mut self, total: u8, soft: bool, cards: u8) -> f64 {
+        if total > 21 {
+            return -1.0;
+        }
+        if self.charlie.is_some_and(|charlie| cards >= charlie) {
+            return 1.0;
+        }
+        if total == 21 {
+            return self.stand_ev(21);
+        }
+        if let Some(&ev) = self.memo.get(&(total, soft, cards)) {
+            return ev;
+        }
+        let stand = self.stand_ev(total);
+        let ev = match *(self.strategy)(total, soft) {
+            // one card short of a Charlie, hitting wins unless it busts, and
+            // a soft hand can't bust
+            Strategy::S | Strategy::DS
+                if self.charlie == Some(cards + 1)
+                    && (soft || 2.0 * self.no_bust(total) - 1.0 > stand) =>
+            {
+                self.hit_ev(total, soft, cards)
+            }
+            Strategy::S => stand,
+            Strategy::D | Strategy::DS if cards == 2 => {
+                2.0 * self.ev_after_one_card(total, soft, cards)
+            }
+            Strategy::DS => stand,
+            Strategy::RH if cards == 2 && self.surrender => -0.5,
+            Strategy::H | Strategy::D | Strategy::RH | Strategy::P | Strategy::PH => {
+                self.hit_ev(total, soft, cards)
+            }
+        };
+        self.memo.insert((total, soft, cards), ev);
+        ev
+    }
+
+    // What splitting a pair of aces is worth, for both hands. The split hands
+    // aren't resplit.
+    pub(super) fn split_aces_ev(&mut self, hit_split_aces: bool) -> f64 {
+        2.0 * (2..=11)
+            .map(|value| {
+                let (total, soft) = draw(11, true, value);
+                self.probabilities[value as usize]
+                    * match hit_split_aces {
+                        true => self.ev(total, soft, 2),
+                        false => self.stand_ev(total),
+                    }
+            })
+            .sum::<f64>()
+    }
+}
+
+impl BlackjackState {
+    // Chance of each dealer outcome given the cards the player can see
+    pub(super) fn dealer_outcomes(&self) -> DealerOutcomes {
+        let upcard = card_value(&self.dealer_hand[0], true);
+        let hole_card = match self.hole_card_exposed() {
+            true => Some(card_value(&self.dealer_hand[1], true)),
+            false => None,
+        };
+        dealer_outcomes(&self.rules, upcard, hole_card, self.dealer_peeked())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackjack::presets::find_preset;
+
+    #[test]
+    fn test_dealer_outcomes() {
+        let mut rules = find_preset("vegas-strip").unwrap().rules;
+        // a dealer 16 draws once: an ace to a five make 17 to 21, anything
+        // else busts
+        let outcomes = dealer_outcomes(&rules, 10, Some(6), false);
+        for chance in &outcomes[17..=21] {
+            assert!((chance - 1.0 / 13.0).abs() < 1e-9);
+        }
+        assert!((outcomes[22] + outcomes[23] - 8.0 / 13.0).abs() < 1e-9);
+        // standing on 17 wins 5/13, standing on 21 loses nothing
+        assert!((stand_ev(17, &outcomes, false) - (8.0 - 4.0) / 13.0).abs() < 1e-9);
+        assert!((stand_ev(21, &outcomes, false) - 12.0 / 13.0).abs() < 1e-9);
+
+        // soft 17 stands, unless the dealer hits it
+        assert_eq!(dealer_outcomes(&rules, 11, Some(6), false)[17], 1.0);
+        rules.dealer_stands_on_all_17 = false;
+        let hits_soft_17 = dealer_outcomes(&rules, 11, Some(6), false);
+        assert!(hits_soft_17[17] < 0.5 && hits_soft_17[18] > 1.0 / 13.0);
+
+        // a dealer who peeked under a ten doesn't have an ace
+        let peeked = dealer_outcomes(&rules, 10, None, true);
+        let not_peeked = dealer_outcomes(&rules, 10, None, false);
+        assert!(peeked[21] < not_peeked[21]);
+        assert!((peeked.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,348 @@
+// Hand histories are stored as JSON lines, one round per line. Each round
+// keeps the cards that were dealt (in dealing order) so the replayer can
+// rebuild the exact same shoe and step through the round again.
+use crate::blackjack::{
+    events::GameEvent,
+    init_state_with_shoe,
+    money::Money,
+    round_net,
+    ruleset::{BlackjackRuleset, RulesetProblem},
+    side_bets::{SideBet, SideBetOutcome},
+    BlackjackState, Card, GameState, HandOutcome, PlayerAction,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HandHistory {
+    pub rules: BlackjackRuleset,
+    pub starting_bet: Money,
+    pub cards: Vec<Card>, // in the order they were dealt
+    pub actions: Vec<PlayerAction>,
+    pub bets: Vec<Money>,
+    pub outcomes: Vec<HandOutcome>,
+    pub net: Money,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub side_bets: Vec<SideBet>,
+}
+
+pub struct HandRecorder {
+    rules: BlackjackRuleset,
+    starting_bet: Money,
+    shoe: Vec<Card>,
+    actions: Vec<PlayerAction>,
+}
+impl HandRecorder {
+    pub fn new(game: &BlackjackState) -> HandRecorder {
+        if !matches!(game.state, GameState::Dealing) || !game.dealer_hand.is_empty() {
+            panic!("Hand recording must start before the first card is dealt.");
+        }
+        HandRecorder {
+            rules: game.rules,
+            starting_bet: game.starting_bet,
+            shoe: game.shoe.clone(),
+            actions: Vec::new(),
+        }
+    }
+
+    pub fn record_action(&mut self, action: PlayerAction) {
+        self.actions.push(action);
+    }
+
+    // Keeps the recording in step with the game after an undo
+    pub fn rewind_to(&mut self, game: &BlackjackState) {
+        self.actions.truncate(game.undo_depth());
+    }
+
+    pub fn finish(self, game: &BlackjackState) -> HandHistory {
+        if !matches!(game.state, GameState::GameOver) {
+            panic!("Game is not over; cannot finish hand history.");
+        }
+        let cards_dealt = self.shoe.len() - game.shoe.len();
+        let cards = self.shoe.iter().rev().take(cards_dealt).cloned().collect();
+        HandHistory {
+            rules: self.rules,
+            starting_bet: self.starting_bet,
+            cards,
+            actions: self.actions,
+            bets: game.bets.clone(),
+            outcomes: game.player_hand_outcomes(),
+            net: round_net(game),
+            side_bets: game.side_bets.clone(),
+        }
+    }
+}
+
+pub fn write_history(writer: &mut impl Write, history: &HandHistory) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, history)?;
+    writeln!(writer)
+}
+
+pub fn read_histories(reader: impl BufRead) -> Result<Vec<HandHistory>, String> {
+    let mut histories = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|err| format!("line {}: {}", i + 1, err))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let history =
+            serde_json::from_str(&line).map_err(|err| format!("line {}: {}", i + 1, err))?;
+        histories.push(history);
+    }
+    Ok(histories)
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    InvalidRuleset(Vec<RulesetProblem>),
+    InvalidSideBet(String),
+    OutOfCards,
+    MissingAction,
+    InvalidAction {
+        action: PlayerAction,
+        allowed_actions: Vec<PlayerAction>,
+    },
+    UnusedActions(usize),
+    UnusedCards(usize),
+    BetMismatch {
+        recorded: Vec<Money>,
+        replayed: Vec<Money>,
+    },
+    OutcomeMismatch {
+        recorded: Vec<HandOutcome>,
+        replayed: Vec<HandOutcome>,
+    },
+    SideBetMismatch {
+        recorded: Vec<Option<SideBetOutcome>>,
+        replayed: Vec<Option<SideBetOutcome>>,
+    },
+    NetMismatch {
+        recorded: Money,
+        replayed: Money,
+    },
+}
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::InvalidRuleset(problems) => {
+                let problems: Vec<String> =
+                    problems.iter().map(|problem| problem.to_string()).collect();
+                write!(f, "invalid ruleset: {}", problems.join("; "))
+            }
+            ReplayError::InvalidSideBet(problem) => write!(f, "{}", problem),
+            ReplayError::OutOfCards => f.write_str("the recorded cards ran out"),
+            ReplayError::MissingAction => f.write_str("no recorded action for the player's turn"),
+            ReplayError::InvalidAction {
+                action,
+                allowed_actions,
+            } => write!(
+                f,
+                "recorded action {:?} is not allowed; valid actions are {:?}",
+                action, allowed_actions
+            ),
+            ReplayError::UnusedActions(n) => write!(f, "{} recorded action(s) were not used", n),
+            ReplayError::UnusedCards(n) => write!(f, "{} recorded card(s) were not dealt", n),
+            ReplayError::BetMismatch { recorded, replayed } => write!(
+                f,
+                "bets differ: recorded {:?}, replayed {:?}",
+                recorded, replayed
+            ),
+            ReplayError::OutcomeMismatch { recorded, replayed } => write!(
+                f,
+                "outcomes differ: recorded {:?}, replayed {:?}",
+                recorded, replayed
+            ),
+            ReplayError::SideBetMismatch { recorded, replayed } => write!(
+                f,
+                "side bets differ: recorded {:?}, replayed {:?}",
+                recorded, replayed
+            ),
+            ReplayError::NetMismatch { recorded, replayed } => write!(
+                f,
+                "net differs: recorded ${}, replayed ${}",
+                recorded, replayed
+            ),
+        }
+    }
+}
+
+pub struct Replay<'a> {
+    history: &'a HandHistory,
+    game: BlackjackState,
+    next_action: usize,
+}
+impl<'a> Replay<'a> {
+    pub fn new(history: &'a HandHistory) -> Result<Replay<'a>, ReplayError> {
+        let shoe = history.cards.iter().rev().cloned().collect();
+        let mut game = init_state_with_shoe(history.starting_bet, history.rules, shoe)
+            .map_err(ReplayError::InvalidRuleset)?;
+        for side_bet in &history.side_bets {
+            game.place_side_bet(side_bet.clone())
+                .map_err(ReplayError::InvalidSideBet)?;
+        }
+        Ok(Replay {
+            history,
+            game,
+            next_action: 0,
+        })
+    }
+
+    pub fn state(&self) -> &BlackjackState {
+        &self.game
+    }
+
+    pub fn finished(&self) -> bool {
+        matches!(self.game.state, GameState::GameOver)
+    }
+
+    // Advances the round by one transition, returning what happened
+    pub fn step(&mut self) -> Result<Vec<GameEvent>, ReplayError> {
+        let action = match self.game.state {
+            GameState::SwitchDecision | GameState::PlayerTurn => {
+                let action = *self
+                    .history
+                    .actions
+                    .get(self.next_action)
+                    .ok_or(ReplayError::MissingAction)?;
+                if !self.game.is_action_allowed(action) {
+                    return Err(ReplayError::InvalidAction {
+                        action,
+                        allowed_actions: self.game.allowed_actions(),
+                    });
+                }
+                self.next_action += 1;
+                Some(action)
+            }
+            _ => None,
+        };
+        self.game
+            .try_next_state_with_events(action)
+            .map_err(|_| ReplayError::OutOfCards)
+    }
+
+    // Checks the finished replay against the recorded settlement
+    pub fn verify(&self) -> Result<(), ReplayError> {
+        let unused_actions = self.history.actions.len() - self.next_action;
+        if unused_actions > 0 {
+            return Err(ReplayError::UnusedActions(unused_actions));
+        }
+        if !self.game.shoe.is_empty() {
+            return Err(ReplayError::UnusedCards(self.game.shoe.len()));
+        }
+        if self.game.bets != self.history.bets {
+            return Err(ReplayError::BetMismatch {
+                recorded: self.history.bets.clone(),
+                replayed: self.game.bets.clone(),
+            });
+        }
+        let outcomes = self.game.player_hand_outcomes();
+        if outcomes != self.history.outcomes {
+            return Err(ReplayError::OutcomeMismatch {
+                recorded: self.history.outcomes.clone(),
+                replayed: outcomes,
+            });
+        }
+        let side_bet_outcomes = |side_bets: &[SideBet]| -> Vec<Option<SideBetOutcome>> {
+            side_bets.iter().map(|side_bet| side_bet.outcome).collect()
+        };
+        let recorded = side_bet_outcomes(&self.history.side_bets);
+        let replayed = side_bet_outcomes(&self.game.side_bets);
+        if recorded != replayed {
+            return Err(ReplayError::SideBetMismatch { recorded, replayed });
+        }
+        let net = round_net(&self.game);
+        if net != self.history.net {
+            return Err(ReplayError::NetMismatch {
+                recorded: self.history.net,
+                replayed: net,
+            });
+        }
+        Ok(())
+    }
+}
+
+pub fn replay(history: &HandHistory) -> Result<BlackjackState, ReplayError> {
+    let mut replay = Replay::new(history)?;
+    while !replay.finished() {
+        replay.step()?;
+    }
+    replay.verify()?;
+    Ok(replay.game)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackjack::{
+        init_state,
+        presets::PRESETS,
+        scenario::{deal_to_player_turn, Scenario},
+    };
+    use crate::monte_carlo::play_optimal_round;
+
+    #[test]
+    fn test_recorded_rounds_replay_to_the_same_outcome() {
+        let mut buffer: Vec<u8> = Vec::new();
+        for _ in 0..200 {
+            let mut game = init_state(Money::from_dollars(1), PRESETS[0].rules).unwrap();
+            let mut recorder = HandRecorder::new(&game);
+            while !matches!(game.state, GameState::GameOver) {
+                if game.state.awaits_player_action() {
+                    let action = game.get_optimal_move();
+                    recorder.record_action(action);
+                    game.next_state(Some(action));
+                } else {
+                    game.next_state(None);
+                }
+            }
+            write_history(&mut buffer, &recorder.finish(&game)).unwrap();
+        }
+        let histories = read_histories(buffer.as_slice()).unwrap();
+        assert_eq!(histories.len(), 200);
+        for history in &histories {
+            replay(history).unwrap();
+        }
+
+        let mut tampered = histories[0].clone();
+        tampered.outcomes.push(HandOutcome::Push);
+        assert!(replay(&tampered).is_err());
+
+        let mut tampered = histories[0].clone();
+        tampered.cards.pop();
+        assert!(matches!(replay(&tampered), Err(ReplayError::OutOfCards)));
+
+        let mut tampered = histories[0].clone();
+        tampered.net += Money::from_dollars(1);
+        assert!(matches!(
+            replay(&tampered),
+            Err(ReplayError::NetMismatch { .. })
+        ));
+
+        let mut tampered = histories[0].clone();
+        tampered.rules.double_after_split = false;
+        tampered.rules.double_on_split_ace = true;
+        assert!(matches!(
+            replay(&tampered),
+            Err(ReplayError::InvalidRuleset(_))
+        ));
+    }
+
+    #[test]
+    fn test_double_for_less_replays() {
+        let mut rules = PRESETS[0].rules;
+        rules.double_for_less = true;
+        let scenario: Scenario = "6s 5h / 6d 7c / Tc Td".parse().unwrap();
+        let game = scenario.build(Money::from_dollars(1), rules, 6).unwrap();
+        let mut recorder = HandRecorder::new(&game);
+        let mut game = deal_to_player_turn(game);
+        let action = PlayerAction::DoubleFor(Money::from_cents(50));
+        recorder.record_action(action);
+        game.next_state(Some(action));
+        play_optimal_round(&mut game);
+        let replayed = replay(&recorder.finish(&game)).unwrap();
+        assert_eq!(replayed.bets, vec![Money::from_cents(150)]);
+    }
+}
@@ -0,0 +1,265 @@
+// Bets and bankrolls are whole cents and payouts are ratios, so settling a
+// round never accumulates floating point error. Both are written as plain
+// numbers in JSON/TOML (dollars, and eg. 1.5 for a 3:2 payout), and a payout
+// may also be written as a ratio string like "6:5".
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    iter::Sum,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
+)]
+#[serde(from = "f64", into = "f64")]
+pub struct Money {
+    cents: i64,
+}
+
+impl Money {
+    pub const ZERO: Money = Money { cents: 0 };
+
+    pub const fn from_cents(cents: i64) -> Money {
+        Money { cents }
+    }
+
+    pub const fn from_dollars(dollars: i64) -> Money {
+        Money {
+            cents: dollars * 100,
+        }
+    }
+
+    pub fn cents(self) -> i64 {
+        self.cents
+    }
+
+    pub fn as_dollars(self) -> f64 {
+        self.cents as f64 / 100f64
+    }
+
+    // Winnings at the given payout. Fractions of a cent are not paid.
+    pub fn times(self, payout: Payout) -> Money {
+        Money {
+            cents: self.cents * payout.numerator as i64 / payout.denominator as i64,
+        }
+    }
+
+    // Half the amount, rounded down to the cent (eg. a surrender refund)
+    pub fn half(self) -> Money {
+        Money {
+            cents: self.cents / 2,
+        }
+    }
+}
+
+impl From<f64> for Money {
+    fn from(dollars: f64) -> Money {
+        Money {
+            cents: (dollars * 100f64).round() as i64,
+        }
+    }
+}
+impl From<Money> for f64 {
+    fn from(money: Money) -> f64 {
+        money.as_dollars()
+    }
+}
+
+impl fmt::Display for Money {
+    // Formats as dollars and cents, eg. "1.50" or with {:+} "+1.50"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = match (self.cents < 0, f.sign_plus()) {
+            (true, _) => "-",
+            (false, true) => "+",
+            (false, false) => "",
+        };
+        let cents = self.cents.unsigned_abs();
+        write!(f, "{}{}.{:02}", sign, cents / 100, cents % 100)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+    fn add(self, other: Money) -> Money {
+        Money::from_cents(self.cents + other.cents)
+    }
+}
+impl Sub for Money {
+    type Output = Money;
+    fn sub(self, other: Money) -> Money {
+        Money::from_cents(self.cents - other.cents)
+    }
+}
+impl Neg for Money {
+    type Output = Money;
+    fn neg(self) -> Money {
+        Money::from_cents(-self.cents)
+    }
+}
+impl Mul<i64> for Money {
+    type Output = Money;
+    fn mul(self, factor: i64) -> Money {
+        Money::from_cents(self.cents * factor)
+    }
+}
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        self.cents += other.cents;
+    }
+}
+impl SubAssign for Money {
+    fn sub_assign(&mut self, other: Money) {
+        self.cents -= other.cents;
+    }
+}
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        iter.copied().sum()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(try_from = "PayoutValue", into = "f64")]
+pub struct Payout {
+    numerator: u32,
+    denominator: u32,
+}
+
+// largest denominator tried when reading a payout written as a decimal
+const MAX_PAYOUT_DENOMINATOR: u32 = 100;
+
+fn gcd(a: u32, b: u32) -> u32 {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+impl Payout {
+    pub const THREE_TO_TWO: Payout = Payout::new(3, 2);
+    pub const SIX_TO_FIVE: Payout = Payout::new(6, 5);
+    pub const EVEN_MONEY: Payout = Payout::new(1, 1);
+
+    pub const fn new(numerator: u32, denominator: u32) -> Payout {
+        if denominator == 0 {
+            panic!("Payout denominator must not be zero.");
+        }
+        Payout {
+            numerator,
+            denominator,
+        }
+    }
+
+    pub fn numerator(self) -> u32 {
+        self.numerator / gcd(self.numerator, self.denominator)
+    }
+
+    pub fn denominator(self) -> u32 {
+        self.denominator / gcd(self.numerator, self.denominator)
+    }
+
+    pub fn as_f64(self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    // Finds the ratio a decimal like 1.2 stands for (6:5)
+    pub fn from_decimal(value: f64) -> Option<Payout> {
+        if !value.is_finite() || value < 0f64 {
+            return None;
+        }
+        (1..=MAX_PAYOUT_DENOMINATOR).find_map(|denominator| {
+            let numerator = value * denominator as f64;
+            match (numerator - numerator.round()).abs() < 1e-4 {
+                true => Some(Payout::new(numerator.round() as u32, denominator)),
+                false => None,
+            }
+        })
+    }
+}
+
+impl PartialEq for Payout {
+    fn eq(&self, other: &Payout) -> bool {
+        self.numerator as u64 * other.denominator as u64
+            == other.numerator as u64 * self.denominator as u64
+    }
+}
+
+impl fmt::Display for Payout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.numerator(), self.denominator())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PayoutValue {
+    Ratio(String),
+    Decimal(f64),
+}
+impl TryFrom<PayoutValue> for Payout {
+    type Error = String;
+
+    fn try_from(value: PayoutValue) -> Result<Payout, String> {
+        match value {
+            PayoutValue::Decimal(value) => Payout::from_decimal(value)
+                .ok_or_else(|| format!("{} is not a payout ratio", value)),
+            PayoutValue::Ratio(text) => {
+                let parts: Vec<&str> = text.split([':', '/']).map(str::trim).collect();
+                match parts[..] {
+                    [numerator, denominator] => {
+                        match (numerator.parse::<u32>(), denominator.parse::<u32>()) {
+                            (Ok(numerator), Ok(denominator)) if denominator > 0 => {
+                                Ok(Payout::new(numerator, denominator))
+                            }
+                            _ => Err(format!("{:?} is not a payout ratio like \"3:2\"", text)),
+                        }
+                    }
+                    _ => Err(format!("{:?} is not a payout ratio like \"3:2\"", text)),
+                }
+            }
+        }
+    }
+}
+impl From<Payout> for f64 {
+    fn from(payout: Payout) -> f64 {
+        payout.as_f64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payouts_are_exact() {
+        let bet = Money::from_dollars(5);
+        assert_eq!(bet.times(Payout::THREE_TO_TWO), Money::from_cents(750));
+        assert_eq!(bet.times(Payout::SIX_TO_FIVE), Money::from_dollars(6));
+        assert_eq!(bet.half(), Money::from_cents(250));
+        let total: Money = std::iter::repeat_n(Money::from_cents(10), 1_000_000).sum();
+        assert_eq!(total, Money::from_dollars(100_000));
+        assert_eq!(format!("{:+}", -Money::from_cents(5)), "-0.05");
+        assert_eq!(format!("{:+}", Money::from_cents(150)), "+1.50");
+    }
+
+    #[test]
+    fn test_payout_from_config_values() {
+        assert_eq!(
+            Payout::from_decimal(1.2f32 as f64),
+            Some(Payout::SIX_TO_FIVE)
+        );
+        assert_eq!(Payout::from_decimal(1.5), Some(Payout::THREE_TO_TWO));
+        let payout: Payout = serde_json::from_str("\"7:5\"").unwrap();
+        assert_eq!(payout.to_string(), "7:5");
+        assert_eq!(serde_json::to_string(&Payout::THREE_TO_TWO).unwrap(), "1.5");
+        assert!(serde_json::from_str::<Payout>("\"3:0\"").is_err());
+        let money: Money = serde_json::from_str("1.1").unwrap();
+        assert_eq!(money, Money::from_cents(110));
+    }
+}
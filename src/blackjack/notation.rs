@@ -0,0 +1,229 @@
+// Compact card notation: a rank (2-9, T or 10, J, Q, K, A) followed by a suit
+// letter (s, h, d, c) or symbol (♠, ♥, ♦, ♣), eg. "As Td 9h" or "A♠ 10♦".
+// Cards in a list may be separated by spaces or commas.
+use crate::blackjack::{Card, Rank, Suit};
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotationError {
+    Empty,
+    InvalidRank(String),
+    InvalidSuit(String),
+    MissingSuit(String),
+}
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NotationError::Empty => f.write_str("no card given"),
+            NotationError::InvalidRank(text) => write!(
+                f,
+                "invalid rank in {:?} (expected 2-9, T, 10, J, Q, K or A)",
+                text
+            ),
+            NotationError::InvalidSuit(text) => write!(
+                f,
+                "invalid suit in {:?} (expected s, h, d, c, ♠, ♥, ♦ or ♣)",
+                text
+            ),
+            NotationError::MissingSuit(text) => write!(f, "missing suit in {:?}", text),
+        }
+    }
+}
+
+impl FromStr for Rank {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "2" => Ok(Rank::Two),
+            "3" => Ok(Rank::Three),
+            "4" => Ok(Rank::Four),
+            "5" => Ok(Rank::Five),
+            "6" => Ok(Rank::Six),
+            "7" => Ok(Rank::Seven),
+            "8" => Ok(Rank::Eight),
+            "9" => Ok(Rank::Nine),
+            "T" | "10" => Ok(Rank::Ten),
+            "J" => Ok(Rank::Jack),
+            "Q" => Ok(Rank::Queen),
+            "K" => Ok(Rank::King),
+            "A" => Ok(Rank::Ace),
+            "" => Err(NotationError::Empty),
+            _ => Err(NotationError::InvalidRank(s.to_string())),
+        }
+    }
+}
+
+impl FromStr for Suit {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "s" | "S" | "♠" | "♤" => Ok(Suit::Spades),
+            "h" | "H" | "♥" | "♡" => Ok(Suit::Hearts),
+            "d" | "D" | "♦" | "♢" => Ok(Suit::Diamonds),
+            "c" | "C" | "♣" | "♧" => Ok(Suit::Clubs),
+            "" => Err(NotationError::Empty),
+            _ => Err(NotationError::InvalidSuit(s.to_string())),
+        }
+    }
+}
+
+impl FromStr for Card {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(NotationError::Empty);
+        }
+        if s.parse::<Rank>().is_ok() {
+            return Err(NotationError::MissingSuit(s.to_string()));
+        }
+        let (suit_start, _) = s.char_indices().last().unwrap();
+        let rank = s[..suit_start]
+            .parse()
+            .map_err(|_| NotationError::InvalidRank(s.to_string()))?;
+        let suit = s[suit_start..]
+            .parse()
+            .map_err(|_| NotationError::InvalidSuit(s.to_string()))?;
+        Ok(Card {
+            suit,
+            rank,
+            face_down: false,
+        })
+    }
+}
+
+// Parses a hand or a sequence of cards, in the order they are written
+pub fn parse_cards(s: &str) -> Result<Vec<Card>, NotationError> {
+    s.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .map(|token| token.parse())
+        .collect()
+}
+
+// Parses cards written in dealing order into a shoe (drawn from the end)
+pub fn parse_shoe(s: &str) -> Result<Vec<Card>, NotationError> {
+    let mut shoe = parse_cards(s)?;
+    shoe.reverse();
+    Ok(shoe)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SuitStyle {
+    Ascii,   // s h d c
+    Unicode, // ♠ ♥ ♦ ♣
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TenStyle {
+    T,
+    Ten,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CardFormat {
+    pub suits: SuitStyle,
+    pub ten: TenStyle,
+}
+impl CardFormat {
+    // what `Display` uses, eg. "10♦"
+    pub const UNICODE: CardFormat = CardFormat {
+        suits: SuitStyle::Unicode,
+        ten: TenStyle::Ten,
+    };
+    // fixed width and plain ASCII, eg. "Td"
+    pub const ASCII: CardFormat = CardFormat {
+        suits: SuitStyle::Ascii,
+        ten: TenStyle::T,
+    };
+}
+
+impl Rank {
+    pub fn format(&self, format: CardFormat) -> String {
+        match (self, format.ten) {
+            (Rank::Ten, TenStyle::T) => "T".to_string(),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl Suit {
+    pub fn format(&self, format: CardFormat) -> String {
+        match format.suits {
+            SuitStyle::Unicode => self.to_string(),
+            SuitStyle::Ascii => match self {
+                Suit::Spades => "s",
+                Suit::Hearts => "h",
+                Suit::Diamonds => "d",
+                Suit::Clubs => "c",
+            }
+            .to_string(),
+        }
+    }
+}
+
+impl Card {
+    // Face down cards are written as "?"
+    pub fn format(&self, format: CardFormat) -> String {
+        if self.face_down {
+            "?".to_string()
+        } else {
+            format!("{}{}", self.rank.format(format), self.suit.format(format))
+        }
+    }
+}
+
+pub fn format_cards(cards: &[Card], format: CardFormat) -> String {
+    cards
+        .iter()
+        .map(|card| card.format(format))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_format_round_trip() {
+        let cards = parse_cards("As Td, 9h  A♠ 10♦ qc").unwrap();
+        assert_eq!(cards.len(), 6);
+        assert_eq!(cards[1].rank, Rank::Ten);
+        assert_eq!(cards[4].suit, Suit::Diamonds);
+        assert_eq!(format_cards(&cards, CardFormat::ASCII), "As Td 9h As Td Qc");
+        assert_eq!(
+            format_cards(&cards, CardFormat::UNICODE),
+            "A♠ 10♦ 9♥ A♠ 10♦ Q♣"
+        );
+        for card in &cards {
+            assert_eq!(card.format(CardFormat::UNICODE), card.to_string());
+        }
+
+        let shoe = parse_shoe("As Td").unwrap();
+        assert_eq!(shoe.last().unwrap().rank, Rank::Ace);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "A".parse::<Card>().unwrap_err(),
+            NotationError::MissingSuit("A".to_string())
+        );
+        assert_eq!(
+            "10".parse::<Card>().unwrap_err(),
+            NotationError::MissingSuit("10".to_string())
+        );
+        assert_eq!(
+            "1s".parse::<Card>().unwrap_err(),
+            NotationError::InvalidRank("1s".to_string())
+        );
+        assert_eq!(
+            "Ax".parse::<Card>().unwrap_err(),
+            NotationError::InvalidSuit("Ax".to_string())
+        );
+        assert!(parse_cards("As 9").is_err());
+    }
+}
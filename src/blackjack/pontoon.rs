@@ -0,0 +1,210 @@
+// British Pontoon: both of the dealer's cards are dealt face down, and the
+// player twists (hits), sticks (stands) or buys a card, raising the bet by the
+// original stake. A hand can't stick under 15 and can't buy after twisting.
+// Hands rank pontoon (a two card 21), then a five-card trick (five cards
+// without busting), then the highest total, and the dealer wins ties.
+use crate::blackjack::{
+    card_value,
+    constants::basic_strategy_tables::{pontoon, Strategy},
+    events::GameEvent,
+    money::Payout,
+    ruleset::GameVariant,
+    BlackjackState, Card, Hand, HandOutcome, HandValue, LossReason, PlayerAction, WinReason,
+};
+
+pub const FIVE_CARD_TRICK_PAYOUT: Payout = Payout::new(2, 1);
+pub const FIVE_CARD_TRICK: usize = 5;
+pub const MIN_STICK_TOTAL: u8 = 15;
+// cards can be bought for a third and fourth card, a fifth is twisted for
+pub const MAX_CARDS_TO_BUY_ON: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+enum PontoonHand {
+    Bust,
+    Total(u8),
+    FiveCardTrick,
+    Pontoon,
+}
+
+fn total(value: HandValue) -> u8 {
+    match value {
+        HandValue::Hard(n) | HandValue::Soft(n) => n,
+        HandValue::Blackjack => 21,
+    }
+}
+
+fn rank(cards: &[Card], value: HandValue) -> PontoonHand {
+    if total(value) > 21 {
+        PontoonHand::Bust
+    } else if matches!(value, HandValue::Blackjack) {
+        PontoonHand::Pontoon
+    } else if cards.len() >= FIVE_CARD_TRICK {
+        PontoonHand::FiveCardTrick
+    } else {
+        PontoonHand::Total(total(value))
+    }
+}
+
+impl BlackjackState {
+    pub fn is_pontoon(&self) -> bool {
+        matches!(self.rules.variant, GameVariant::Pontoon)
+    }
+
+    fn can_buy(&self, hand: &Hand, can_hit: bool) -> bool {
+        let split_rule_satisfied = match (hand.from_split, hand.split_aces) {
+            (false, _) => true,
+            (true, false) => self.rules.double_after_split,
+            (true, true) => self.rules.double_on_split_ace,
+        };
+        let twisted = hand.cards.len() > 2 + hand.bought as usize;
+        can_hit && split_rule_satisfied && !twisted && hand.cards.len() <= MAX_CARDS_TO_BUY_ON
+    }
+
+    pub(super) fn pontoon_actions(&self, can_hit: bool, can_split: bool) -> Vec<PlayerAction> {
+        let hand = &self.player_hands[self.hand_index];
+        let mut allowed_actions = Vec::with_capacity(4);
+        if can_hit {
+            allowed_actions.push(PlayerAction::Twist);
+        }
+        if total(self.player_hand_value(hand)) >= MIN_STICK_TOTAL || !can_hit {
+            allowed_actions.push(PlayerAction::Stick);
+        }
+        if self.can_buy(hand, can_hit) {
+            allowed_actions.push(PlayerAction::Buy);
+        }
+        if can_split {
+            allowed_actions.push(PlayerAction::Split);
+        }
+        allowed_actions
+    }
+
+    // Raises the current hand's bet by the original stake for the card it buys
+    pub(super) fn pay_for_bought_card(&mut self) {
+        self.bets[self.hand_index] += self.starting_bet;
+        self.player_hands[self.hand_index].bought += 1;
+        self.emit(GameEvent::CardBought {
+            hand_index: self.hand_index,
+            bet: self.bets[self.hand_index],
+        });
+    }
+
+    pub(super) fn pontoon_optimal_move(&self, allowed_actions: &[PlayerAction]) -> PlayerAction {
+        let hand = &self.player_hands[self.hand_index];
+        let can_split = allowed_actions.contains(&PlayerAction::Split);
+        let strategy = if can_split {
+            &pontoon::SPLIT[card_value(&hand.cards[0], true) as usize - 2]
+        } else {
+            let column = hand.cards.len().min(4) - 2;
+            match self.player_hand_value(hand) {
+                HandValue::Hard(n) => &pontoon::HARD[n.max(8) as usize - 8][column],
+                HandValue::Soft(n) => &pontoon::SOFT[n as usize - 12][column],
+                HandValue::Blackjack => panic!("Unreachable code."),
+            }
+        };
+        let twist_or_stick = |strategy: &Strategy| match strategy {
+            Strategy::S | Strategy::DS if allowed_actions.contains(&PlayerAction::Stick) => {
+                PlayerAction::Stick
+            }
+            _ if allowed_actions.contains(&PlayerAction::Twist) => PlayerAction::Twist,
+            _ => PlayerAction::Stick,
+        };
+        match strategy {
+            Strategy::P => PlayerAction::Split,
+            Strategy::D | Strategy::DS if allowed_actions.contains(&PlayerAction::Buy) => {
+                PlayerAction::Buy
+            }
+            strategy => twist_or_stick(strategy),
+        }
+    }
+
+    pub(super) fn pontoon_outcome(&self, hand: &Hand) -> HandOutcome {
+        let player = rank(&hand.cards, self.player_hand_value(hand));
+        // ranked with every card, even one left face down when the player busts
+        let dealer = rank(
+            &self.dealer_hand,
+            self.dealer_hand_value(&self.dealer_hand, true),
+        );
+        match (player, dealer) {
+            (PontoonHand::Bust, _) => HandOutcome::Lost(LossReason::Bust),
+            (PontoonHand::Pontoon, PontoonHand::Pontoon) => {
+                HandOutcome::Lost(LossReason::DealerBlackjack)
+            }
+            _ if player == dealer => HandOutcome::Lost(LossReason::Tie),
+            _ if player < dealer => match dealer {
+                PontoonHand::Pontoon => HandOutcome::Lost(LossReason::DealerBlackjack),
+                _ => HandOutcome::Lost(LossReason::LowerHand),
+            },
+            (PontoonHand::Pontoon, _) => HandOutcome::Won(WinReason::Blackjack),
+            (PontoonHand::FiveCardTrick, _) => HandOutcome::Won(WinReason::FiveCardTrick),
+            (_, PontoonHand::Bust) => HandOutcome::Won(WinReason::DealerBust),
+            _ => HandOutcome::Won(WinReason::HigherHand),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blackjack::{
+        money::Money, scenario, HandOutcome, LossReason, PlayerAction, WinReason,
+    };
+    use crate::monte_carlo::{play_optimal_round, round_net};
+
+    #[test]
+    fn test_buy_then_twist_to_a_five_card_trick() {
+        // 3-2 against a dealer 10-9, neither dealer card visible
+        let mut game = scenario::play_preset("pontoon", "3c 2d / Th 9s / 4h 2s 5c");
+        assert!(game.dealer_hand.iter().all(|card| card.face_down));
+        assert_eq!(
+            game.allowed_actions(),
+            vec![PlayerAction::Twist, PlayerAction::Buy]
+        );
+        assert_eq!(game.get_optimal_move(), PlayerAction::Buy);
+        game.next_state(Some(PlayerAction::Buy));
+        game.next_state(Some(PlayerAction::Twist));
+        // a hand that has twisted can't buy
+        assert_eq!(game.allowed_actions(), vec![PlayerAction::Twist]);
+        game.next_state(Some(PlayerAction::Twist));
+        play_optimal_round(&mut game);
+        assert_eq!(
+            game.player_hand_outcomes(),
+            vec![HandOutcome::Won(WinReason::FiveCardTrick)]
+        );
+        assert_eq!(game.bets, vec![Money::from_dollars(2)]);
+        assert_eq!(round_net(&game), Money::from_dollars(4));
+    }
+
+    #[test]
+    fn test_pontoon_against_dealer_pontoon() {
+        let mut game = scenario::play_preset("pontoon", "Ac Kd / Ah Ks");
+        play_optimal_round(&mut game);
+        assert!(game.dealer_hand.iter().all(|card| !card.face_down));
+        assert_eq!(
+            game.player_hand_outcomes(),
+            vec![HandOutcome::Lost(LossReason::DealerBlackjack)]
+        );
+        assert_eq!(round_net(&game), Money::from_dollars(-1));
+    }
+
+    #[test]
+    fn test_pontoon_against_dealer_total() {
+        let mut game = scenario::play_preset("pontoon", "Ac Kd / Th 6s");
+        play_optimal_round(&mut game);
+        assert!(game.dealer_hand.iter().all(|card| !card.face_down));
+        assert_eq!(
+            game.player_hand_outcomes(),
+            vec![HandOutcome::Won(WinReason::Blackjack)]
+        );
+        assert_eq!(round_net(&game), Money::from_dollars(2));
+    }
+
+    #[test]
+    fn test_dealer_wins_ties() {
+        let mut game = scenario::play_preset("pontoon", "Tc 8d / 9h 9s");
+        game.next_state(Some(PlayerAction::Stick));
+        play_optimal_round(&mut game);
+        assert_eq!(
+            game.player_hand_outcomes(),
+            vec![HandOutcome::Lost(LossReason::Tie)]
+        );
+    }
+}
@@ -0,0 +1,473 @@
+use crate::blackjack::{
+    money::Payout,
+    ruleset::{
+        BlackjackRuleset, DoubleDownOn, GameVariant, MaxHandsAfterSplit, PairSplits, PeekPolicy,
+        SplitAces,
+    },
+};
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct RulesetPreset {
+    pub name: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub num_decks: usize,
+    pub rules: BlackjackRuleset,
+}
+
+// the first preset is used when no ruleset is selected
+pub const PRESETS: [RulesetPreset; 13] = [
+    RulesetPreset {
+        name: "default",
+        title: "Analyzer default",
+        description: "8 decks, dealer stands on soft 17, late surrender, split aces twice, DAS.",
+        num_decks: 8,
+        rules: BlackjackRuleset {
+            variant: GameVariant::Standard,
+
+            surrender: true,
+
+            dealer_stands_on_all_17: true,
+            dealer_peeks: PeekPolicy::Both,
+            dealer_policy: None,
+
+            split_aces: SplitAces::Twice,
+            hit_on_split_ace: false,
+            max_hands_after_split: MaxHandsAfterSplit::Three,
+            split_unlike_tens: false,
+            pair_splits: PairSplits::ANY,
+
+            double_down_on: DoubleDownOn::Any,
+            double_after_split: true,
+            double_on_split_ace: false,
+            double_for_less: false,
+            multi_card_double: None,
+
+            blackjack_payout: Payout::THREE_TO_TWO,
+            ace_and_ten_counts_as_blackjack: true,
+            split_ace_can_be_blackjack: false,
+
+            charlie: None,
+        },
+    },
+    RulesetPreset {
+        name: "vegas-strip",
+        title: "Las Vegas Strip",
+        description: "6 decks, dealer stands on soft 17, late surrender, split to 4 hands, DAS.",
+        num_decks: 6,
+        rules: BlackjackRuleset {
+            variant: GameVariant::Standard,
+
+            surrender: true,
+
+            dealer_stands_on_all_17: true,
+            dealer_peeks: PeekPolicy::Both,
+            dealer_policy: None,
+
+            split_aces: SplitAces::Once,
+            hit_on_split_ace: false,
+            max_hands_after_split: MaxHandsAfterSplit::Four,
+            split_unlike_tens: false,
+            pair_splits: PairSplits::ANY,
+
+            double_down_on: DoubleDownOn::Any,
+            double_after_split: true,
+            double_on_split_ace: false,
+            double_for_less: false,
+            multi_card_double: None,
+
+            blackjack_payout: Payout::THREE_TO_TWO,
+            ace_and_ten_counts_as_blackjack: true,
+            split_ace_can_be_blackjack: false,
+
+            charlie: None,
+        },
+    },
+    RulesetPreset {
+        name: "downtown-vegas",
+        title: "Downtown Las Vegas",
+        description: "2 decks, dealer hits soft 17, no surrender, split to 4 hands, DAS.",
+        num_decks: 2,
+        rules: BlackjackRuleset {
+            variant: GameVariant::Standard,
+
+            surrender: false,
+
+            dealer_stands_on_all_17: false,
+            dealer_peeks: PeekPolicy::Both,
+            dealer_policy: None,
+
+            split_aces: SplitAces::Once,
+            hit_on_split_ace: false,
+            max_hands_after_split: MaxHandsAfterSplit::Four,
+            split_unlike_tens: false,
+            pair_splits: PairSplits::ANY,
+
+            double_down_on: DoubleDownOn::Any,
+            double_after_split: true,
+            double_on_split_ace: false,
+            double_for_less: false,
+            multi_card_double: None,
+
+            blackjack_payout: Payout::THREE_TO_TWO,
+            ace_and_ten_counts_as_blackjack: true,
+            split_ace_can_be_blackjack: false,
+
+            charlie: None,
+        },
+    },
+    RulesetPreset {
+        name: "atlantic-city",
+        title: "Atlantic City",
+        description: "8 decks, dealer stands on soft 17, late surrender, split to 4 hands, DAS.",
+        num_decks: 8,
+        rules: BlackjackRuleset {
+            variant: GameVariant::Standard,
+
+            surrender: true,
+
+            dealer_stands_on_all_17: true,
+            dealer_peeks: PeekPolicy::Both,
+            dealer_policy: None,
+
+            split_aces: SplitAces::Once,
+            hit_on_split_ace: false,
+            max_hands_after_split: MaxHandsAfterSplit::Four,
+            split_unlike_tens: false,
+            pair_splits: PairSplits::ANY,
+
+            double_down_on: DoubleDownOn::Any,
+            double_after_split: true,
+            double_on_split_ace: false,
+            double_for_less: false,
+            multi_card_double: None,
+
+            blackjack_payout: Payout::THREE_TO_TWO,
+            ace_and_ten_counts_as_blackjack: true,
+            split_ace_can_be_blackjack: false,
+
+            charlie: None,
+        },
+    },
+    RulesetPreset {
+        name: "reno",
+        title: "Reno / Lake Tahoe",
+        description: "6 decks, dealer hits soft 17, double on 10 or 11 only, no surrender.",
+        num_decks: 6,
+        rules: BlackjackRuleset {
+            variant: GameVariant::Standard,
+
+            surrender: false,
+
+            dealer_stands_on_all_17: false,
+            dealer_peeks: PeekPolicy::Both,
+            dealer_policy: None,
+
+            split_aces: SplitAces::Once,
+            hit_on_split_ace: false,
+            max_hands_after_split: MaxHandsAfterSplit::Four,
+            split_unlike_tens: false,
+            pair_splits: PairSplits::ANY,
+
+            double_down_on: DoubleDownOn::TenEleven,
+            double_after_split: true,
+            double_on_split_ace: false,
+            double_for_less: false,
+            multi_card_double: None,
+
+            blackjack_payout: Payout::THREE_TO_TWO,
+            ace_and_ten_counts_as_blackjack: true,
+            split_ace_can_be_blackjack: false,
+
+            charlie: None,
+        },
+    },
+    RulesetPreset {
+        name: "european",
+        title: "European (no hole card)",
+        description:
+            "6 decks, no dealer peek, double on 9-11 only, split to 3 hands, no surrender.",
+        num_decks: 6,
+        rules: BlackjackRuleset {
+            variant: GameVariant::Standard,
+
+            surrender: false,
+
+            dealer_stands_on_all_17: true,
+            dealer_peeks: PeekPolicy::None,
+            dealer_policy: None,
+
+            split_aces: SplitAces::Once,
+            hit_on_split_ace: false,
+            max_hands_after_split: MaxHandsAfterSplit::Three,
+            split_unlike_tens: false,
+            pair_splits: PairSplits::ANY,
+
+            double_down_on: DoubleDownOn::NineTenEleven,
+            double_after_split: true,
+            double_on_split_ace: false,
+            double_for_less: false,
+            multi_card_double: None,
+
+            blackjack_payout: Payout::THREE_TO_TWO,
+            ace_and_ten_counts_as_blackjack: true,
+            split_ace_can_be_blackjack: false,
+
+            charlie: None,
+        },
+    },
+    RulesetPreset {
+        name: "macau",
+        title: "Macau",
+        description: "6 decks, no dealer peek, surrender allowed, split to 4 hands, DAS.",
+        num_decks: 6,
+        rules: BlackjackRuleset {
+            variant: GameVariant::Standard,
+
+            surrender: true,
+
+            dealer_stands_on_all_17: true,
+            dealer_peeks: PeekPolicy::None,
+            dealer_policy: None,
+
+            split_aces: SplitAces::Once,
+            hit_on_split_ace: false,
+            max_hands_after_split: MaxHandsAfterSplit::Four,
+            split_unlike_tens: false,
+            pair_splits: PairSplits::ANY,
+
+            double_down_on: DoubleDownOn::Any,
+            double_after_split: true,
+            double_on_split_ace: false,
+            double_for_less: false,
+            multi_card_double: None,
+
+            blackjack_payout: Payout::THREE_TO_TWO,
+            ace_and_ten_counts_as_blackjack: true,
+            split_ace_can_be_blackjack: false,
+
+            charlie: None,
+        },
+    },
+    RulesetPreset {
+        name: "single-deck-6-5",
+        title: "6:5 single deck",
+        description: "1 deck, blackjack pays 6:5, dealer hits soft 17, no DAS, no surrender.",
+        num_decks: 1,
+        rules: BlackjackRuleset {
+            variant: GameVariant::Standard,
+
+            surrender: false,
+
+            dealer_stands_on_all_17: false,
+            dealer_peeks: PeekPolicy::Both,
+            dealer_policy: None,
+
+            split_aces: SplitAces::Once,
+            hit_on_split_ace: false,
+            max_hands_after_split: MaxHandsAfterSplit::Four,
+            split_unlike_tens: false,
+            pair_splits: PairSplits::ANY,
+
+            double_down_on: DoubleDownOn::Any,
+            double_after_split: false,
+            double_on_split_ace: false,
+            double_for_less: false,
+            multi_card_double: None,
+
+            blackjack_payout: Payout::SIX_TO_FIVE,
+            ace_and_ten_counts_as_blackjack: true,
+            split_ace_can_be_blackjack: false,
+
+            charlie: None,
+        },
+    },
+    RulesetPreset {
+        name: "spanish-21",
+        title: "Spanish 21",
+        description: concat!(
+            "6 decks without tens, dealer stands on soft 17, bonus 21s, ",
+            "redoubling, surrender after doubling."
+        ),
+        num_decks: 6,
+        rules: BlackjackRuleset {
+            variant: GameVariant::Spanish21,
+
+            surrender: true,
+
+            dealer_stands_on_all_17: true,
+            dealer_peeks: PeekPolicy::Both,
+            dealer_policy: None,
+
+            split_aces: SplitAces::Thrice,
+            hit_on_split_ace: true,
+            max_hands_after_split: MaxHandsAfterSplit::Four,
+            split_unlike_tens: false,
+            pair_splits: PairSplits::ANY,
+
+            double_down_on: DoubleDownOn::Any,
+            double_after_split: true,
+            double_on_split_ace: true,
+            double_for_less: false,
+            multi_card_double: None,
+
+            blackjack_payout: Payout::THREE_TO_TWO,
+            ace_and_ten_counts_as_blackjack: true,
+            split_ace_can_be_blackjack: false,
+
+            charlie: None,
+        },
+    },
+    RulesetPreset {
+        name: "free-bet",
+        title: "Free Bet Blackjack",
+        description: concat!(
+            "6 decks, free doubles on 9-11 and free splits except tens, ",
+            "dealer 22 pushes, dealer hits soft 17."
+        ),
+        num_decks: 6,
+        rules: BlackjackRuleset {
+            variant: GameVariant::FreeBet,
+
+            surrender: false,
+
+            dealer_stands_on_all_17: false,
+            dealer_peeks: PeekPolicy::Both,
+            dealer_policy: None,
+
+            split_aces: SplitAces::Once,
+            hit_on_split_ace: false,
+            max_hands_after_split: MaxHandsAfterSplit::Four,
+            split_unlike_tens: false,
+            pair_splits: PairSplits::ANY,
+
+            double_down_on: DoubleDownOn::Any,
+            double_after_split: true,
+            double_on_split_ace: false,
+            double_for_less: false,
+            multi_card_double: None,
+
+            blackjack_payout: Payout::THREE_TO_TWO,
+            ace_and_ten_counts_as_blackjack: true,
+            split_ace_can_be_blackjack: false,
+
+            charlie: None,
+        },
+    },
+    RulesetPreset {
+        name: "switch",
+        title: "Blackjack Switch",
+        description: concat!(
+            "6 decks, two hands with switched second cards, blackjack pays 1:1, ",
+            "dealer 22 pushes, dealer hits soft 17."
+        ),
+        num_decks: 6,
+        rules: BlackjackRuleset {
+            variant: GameVariant::Switch,
+
+            surrender: false,
+
+            dealer_stands_on_all_17: false,
+            dealer_peeks: PeekPolicy::Both,
+            dealer_policy: None,
+
+            split_aces: SplitAces::Once,
+            hit_on_split_ace: false,
+            max_hands_after_split: MaxHandsAfterSplit::Four,
+            split_unlike_tens: false,
+            pair_splits: PairSplits::ANY,
+
+            double_down_on: DoubleDownOn::Any,
+            double_after_split: true,
+            double_on_split_ace: false,
+            double_for_less: false,
+            multi_card_double: None,
+
+            blackjack_payout: Payout::EVEN_MONEY,
+            ace_and_ten_counts_as_blackjack: true,
+            split_ace_can_be_blackjack: false,
+
+            charlie: None,
+        },
+    },
+    RulesetPreset {
+        name: "double-exposure",
+        title: "Double Exposure",
+        description: concat!(
+            "6 decks, both dealer cards face up, dealer wins ties, ",
+            "blackjack pays 1:1, double on 9-11 only, dealer hits soft 17."
+        ),
+        num_decks: 6,
+        rules: BlackjackRuleset {
+            variant: GameVariant::DoubleExposure,
+
+            surrender: false,
+
+            dealer_stands_on_all_17: false,
+            dealer_peeks: PeekPolicy::Both,
+            dealer_policy: None,
+
+            split_aces: SplitAces::Once,
+            hit_on_split_ace: false,
+            max_hands_after_split: MaxHandsAfterSplit::Four,
+            split_unlike_tens: false,
+            pair_splits: PairSplits::ANY,
+
+            double_down_on: DoubleDownOn::NineTenEleven,
+            double_after_split: true,
+            double_on_split_ace: false,
+            double_for_less: false,
+            multi_card_double: None,
+
+            blackjack_payout: Payout::EVEN_MONEY,
+            ace_and_ten_counts_as_blackjack: true,
+            split_ace_can_be_blackjack: false,
+
+            charlie: None,
+        },
+    },
+    RulesetPreset {
+        name: "pontoon",
+        title: "British Pontoon",
+        description: concat!(
+            "6 decks, dealer cards face down, twist, stick or buy, ",
+            "pontoon and five-card trick pay 2:1, dealer wins ties, ",
+            "dealer hits soft 17."
+        ),
+        num_decks: 6,
+        rules: BlackjackRuleset {
+            variant: GameVariant::Pontoon,
+
+            surrender: false,
+
+            dealer_stands_on_all_17: false,
+            dealer_peeks: PeekPolicy::Both,
+            dealer_policy: None,
+
+            split_aces: SplitAces::Once,
+            hit_on_split_ace: true,
+            max_hands_after_split: MaxHandsAfterSplit::Four,
+            split_unlike_tens: false,
+            pair_splits: PairSplits::ANY,
+
+            double_down_on: DoubleDownOn::Any,
+            double_after_split: true,
+            double_on_split_ace: true,
+            double_for_less: false,
+            multi_card_double: None,
+
+            blackjack_payout: Payout::new(2, 1),
+            ace_and_ten_counts_as_blackjack: true,
+            split_ace_can_be_blackjack: false,
+
+            charlie: None,
+        },
+    },
+];
+
+pub fn find_preset(name: &str) -> Option<&'static RulesetPreset> {
+    PRESETS
+        .iter()
+        .find(|preset| preset.name.eq_ignore_ascii_case(name.trim()))
+}
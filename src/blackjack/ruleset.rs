@@ -1,13 +1,19 @@
-use serde::{Deserialize, Serialize};
+use crate::blackjack::{
+    constants::{SPANISH_DECK, UNSHUFFLED_DECK},
+    money::Payout,
+    Card, HandValue,
+};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum SplitAces {
     NotAllowed,
     Once,
     Twice,
     Thrice,
 }
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum MaxHandsAfterSplit {
     One,
     Two,
@@ -15,6 +21,34 @@ pub enum MaxHandsAfterSplit {
     Four,
 }
 
+// Times a pair of each value, 2 to 10, can be split in a round, eg. 1 for no
+// resplitting (aces are governed by `split_aces`). Splits are also limited by
+// `max_hands_after_split`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(transparent)]
+pub struct PairSplits(pub [u8; 9]);
+
+// Up cards the dealer checks for a blackjack under before the player acts
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum PeekPolicy {
+    None,
+    AceOnly,
+    TenOnly,
+    Both,
+}
+
+// When the dealer stops drawing and how a dealer 22 is settled
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct DealerPolicy {
+    // lowest soft total the dealer stands on, eg. 18 when hitting soft 17
+    pub stands_on_soft: u8,
+    // lowest hard total the dealer stands on
+    pub stands_on_hard: u8,
+    // a dealer 22 pushes the hands that are still standing
+    #[serde(default)]
+    pub push_22: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub enum DoubleDownOn {
     Any,
@@ -22,26 +56,409 @@ pub enum DoubleDownOn {
     TenEleven,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum GameVariant {
+    #[default]
+    Standard,
+    // 48 card decks, player 21 always wins, bonus 21s, redoubling and
+    // surrendering a doubled hand
+    Spanish21,
+    // free doubles and splits paid for by the house, dealer 22 pushes
+    FreeBet,
+    // two hands whose second cards may be swapped, dealer 22 pushes
+    Switch,
+    // both dealer cards are dealt face up, the dealer wins ties
+    DoubleExposure,
+    // British Pontoon: both dealer cards face down, twist, stick and buy
+    // instead of hit, stand and double, five-card tricks, the dealer wins ties
+    Pontoon,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub struct BlackjackRuleset {
+    #[serde(default)]
+    pub variant: GameVariant,
+
     pub surrender: bool,
 
     // dealer
     pub dealer_stands_on_all_17: bool,
-    pub dealer_peeks: bool,
+    #[serde(deserialize_with = "deserialize_peek_policy")]
+    pub dealer_peeks: PeekPolicy,
+    // replaces `dealer_stands_on_all_17` and the variant's dealer 22 push,
+    // eg. for promotional games where the dealer hits soft 18
+    #[serde(default)]
+    pub dealer_policy: Option<DealerPolicy>,
 
     // splitting
     pub split_aces: SplitAces,
     pub hit_on_split_ace: bool,
     pub max_hands_after_split: MaxHandsAfterSplit,
+    // any two ten-value cards form a pair, eg. K-Q
+    #[serde(default)]
+    pub split_unlike_tens: bool,
+    #[serde(default)]
+    pub pair_splits: PairSplits,
 
     // doubling
     pub double_down_on: DoubleDownOn,
     pub double_after_split: bool,
     pub double_on_split_ace: bool,
+    // doubling for any amount up to the hand's bet
+    #[serde(default)]
+    pub double_for_less: bool,
+    // doubling on hands of up to this many cards, eg. 3 to double after a
+    // hit, rather than only on two cards
+    #[serde(default)]
+    pub multi_card_double: Option<u8>,
 
     // blackjack
-    pub blackjack_payout: f32,
+    pub blackjack_payout: Payout,
     pub ace_and_ten_counts_as_blackjack: bool,
     pub split_ace_can_be_blackjack: bool,
+
+    // a hand that reaches this many cards without busting wins, eg. 5 for a
+    // five-card Charlie
+    #[serde(default)]
+    pub charlie: Option<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "kind")]
+pub enum RulesetProblem {
+    InvalidBlackjackPayout {
+        payout: Payout,
+    },
+    HitOnSplitAceWithoutSplittingAces,
+    DoubleOnSplitAceWithoutSplittingAces,
+    DoubleOnSplitAceWithoutDoubleAfterSplit,
+    SplitAceBlackjackWithoutSplittingAces,
+    AceSplitsExceedMaxHands {
+        split_aces: SplitAces,
+        max_hands_after_split: MaxHandsAfterSplit,
+    },
+    CharlieTooFewCards {
+        cards: u8,
+    },
+    MultiCardDoubleTooFewCards {
+        cards: u8,
+    },
+    InvalidDealerPolicy {
+        policy: DealerPolicy,
+    },
+}
+impl fmt::Display for RulesetProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RulesetProblem::InvalidBlackjackPayout { payout } => {
+                write!(f, "blackjack_payout must be positive (got {})", payout)
+            }
+            RulesetProblem::HitOnSplitAceWithoutSplittingAces => {
+                f.write_str("hit_on_split_ace is set but split_aces is NotAllowed")
+            }
+            RulesetProblem::DoubleOnSplitAceWithoutSplittingAces => {
+                f.write_str("double_on_split_ace is set but split_aces is NotAllowed")
+            }
+            RulesetProblem::DoubleOnSplitAceWithoutDoubleAfterSplit => {
+                f.write_str("double_on_split_ace is set but double_after_split is not")
+            }
+            RulesetProblem::SplitAceBlackjackWithoutSplittingAces => {
+                f.write_str("split_ace_can_be_blackjack is set but split_aces is NotAllowed")
+            }
+            RulesetProblem::AceSplitsExceedMaxHands {
+                split_aces,
+                max_hands_after_split,
+            } => write!(
+                f,
+                "split_aces is {:?} but max_hands_after_split is {:?}",
+                split_aces, max_hands_after_split
+            ),
+            RulesetProblem::CharlieTooFewCards { cards } => {
+                write!(f, "charlie must be at least 3 cards (got {})", cards)
+            }
+            RulesetProblem::InvalidDealerPolicy { policy } => write!(
+                f,
+                "dealer_policy must stand on a soft total of 12 to 21 and a hard total of 12 to 21 (got soft {} and hard {})",
+                policy.stands_on_soft, policy.stands_on_hard
+            ),
+            RulesetProblem::MultiCardDoubleTooFewCards { cards } => {
+                write!(
+                    f,
+                    "multi_card_double must be at least 3 cards (got {})",
+                    cards
+                )
+            }
+        }
+    }
+}
+
+impl SplitAces {
+    pub fn max_splits(&self) -> usize {
+        match self {
+            SplitAces::NotAllowed => 0,
+            SplitAces::Once => 1,
+            SplitAces::Twice => 2,
+            SplitAces::Thrice => 3,
+        }
+    }
+
+    fn from_max_splits(splits: usize) -> SplitAces {
+        match splits {
+            0 => SplitAces::NotAllowed,
+            1 => SplitAces::Once,
+            2 => SplitAces::Twice,
+            _ => SplitAces::Thrice,
+        }
+    }
+}
+
+impl PairSplits {
+    // resplitting up to the hand limit
+    pub const ANY: PairSplits = PairSplits([3; 9]);
+    // every pair can be split once, eg. "split only once except aces"
+    pub const ONCE: PairSplits = PairSplits([1; 9]);
+
+    pub fn max_splits(&self, value: u8) -> usize {
+        self.0[value as usize - 2] as usize
+    }
+
+    // Limits pairs of `value` to `splits`, eg. `(8, 1)` for no resplitting 8s
+    pub const fn with_max_splits(self, value: u8, splits: u8) -> PairSplits {
+        let mut limits = self.0;
+        limits[value as usize - 2] = splits;
+        PairSplits(limits)
+    }
+}
+impl Default for PairSplits {
+    fn default() -> Self {
+        PairSplits::ANY
+    }
+}
+
+impl PeekPolicy {
+    // `upcard` is the up card's value with aces as 11
+    pub fn peeks_under(&self, upcard: u8) -> bool {
+        matches!(
+            (self, upcard),
+            (PeekPolicy::Both, 10 | 11) | (PeekPolicy::AceOnly, 11) | (PeekPolicy::TenOnly, 10)
+        )
+    }
+}
+
+// Rulesets saved before the peek policy have `dealer_peeks` as a bool
+fn deserialize_peek_policy<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<PeekPolicy, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum PeekSetting {
+        Peeks(bool),
+        Policy(PeekPolicy),
+    }
+    Ok(match PeekSetting::deserialize(deserializer)? {
+        PeekSetting::Peeks(true) => PeekPolicy::Both,
+        PeekSetting::Peeks(false) => PeekPolicy::None,
+        PeekSetting::Policy(policy) => policy,
+    })
+}
+
+impl DealerPolicy {
+    pub const STANDS_ON_SOFT_17: DealerPolicy = DealerPolicy {
+        stands_on_soft: 17,
+        stands_on_hard: 17,
+        push_22: false,
+    };
+    pub const HITS_SOFT_17: DealerPolicy = DealerPolicy {
+        stands_on_soft: 18,
+        ..DealerPolicy::STANDS_ON_SOFT_17
+    };
+
+    pub fn stands_on(&self, value: HandValue) -> bool {
+        match value {
+            HandValue::Soft(n) => n >= self.stands_on_soft,
+            HandValue::Hard(n) => n >= self.stands_on_hard,
+            HandValue::Blackjack => true,
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        (12..=21).contains(&self.stands_on_soft) && (12..=21).contains(&self.stands_on_hard)
+    }
+}
+
+impl GameVariant {
+    pub fn deck(&self) -> &'static [Card] {
+        match self {
+            GameVariant::Spanish21 => &SPANISH_DECK,
+            _ => &UNSHUFFLED_DECK,
+        }
+    }
+
+    // Number of hands the player is dealt at the start of a round
+    pub fn starting_hands(&self) -> usize {
+        match self {
+            GameVariant::Switch => 2,
+            _ => 1,
+        }
+    }
+
+    // Whether a dealer 22 pushes the hands that are still standing
+    pub fn dealer_22_pushes(&self) -> bool {
+        matches!(self, GameVariant::FreeBet | GameVariant::Switch)
+    }
+}
+
+impl MaxHandsAfterSplit {
+    pub fn max_hands(&self) -> usize {
+        match self {
+            MaxHandsAfterSplit::One => 1,
+            MaxHandsAfterSplit::Two => 2,
+            MaxHandsAfterSplit::Three => 3,
+            MaxHandsAfterSplit::Four => 4,
+        }
+    }
+}
+
+impl BlackjackRuleset {
+    // The dealer policy in effect, from `dealer_policy` if set or else
+    // `dealer_stands_on_all_17` and the variant
+    pub fn effective_dealer_policy(&self) -> DealerPolicy {
+        self.dealer_policy.unwrap_or(DealerPolicy {
+            push_22: self.variant.dealer_22_pushes(),
+            ..match self.dealer_stands_on_all_17 {
+                true => DealerPolicy::STANDS_ON_SOFT_17,
+                false => DealerPolicy::HITS_SOFT_17,
+            }
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<RulesetProblem>> {
+        let mut problems = Vec::new();
+        let no_ace_splits = matches!(self.split_aces, SplitAces::NotAllowed);
+        if self.blackjack_payout.numerator() == 0 {
+            problems.push(RulesetProblem::InvalidBlackjackPayout {
+                payout: self.blackjack_payout,
+            });
+        }
+        if self.hit_on_split_ace && no_ace_splits {
+            problems.push(RulesetProblem::HitOnSplitAceWithoutSplittingAces);
+        }
+        if self.double_on_split_ace && no_ace_splits {
+            problems.push(RulesetProblem::DoubleOnSplitAceWithoutSplittingAces);
+        }
+        if self.double_on_split_ace && !self.double_after_split {
+            problems.push(RulesetProblem::DoubleOnSplitAceWithoutDoubleAfterSplit);
+        }
+        if self.split_ace_can_be_blackjack && no_ace_splits {
+            problems.push(RulesetProblem::SplitAceBlackjackWithoutSplittingAces);
+        }
+        if self.split_aces.max_splits() + 1 > self.max_hands_after_split.max_hands() {
+            problems.push(RulesetProblem::AceSplitsExceedMaxHands {
+                split_aces: self.split_aces,
+                max_hands_after_split: self.max_hands_after_split,
+            });
+        }
+        if let Some(cards) = self.charlie.filter(|&cards| cards < 3) {
+            problems.push(RulesetProblem::CharlieTooFewCards { cards });
+        }
+        if let Some(policy) = self.dealer_policy.filter(|policy| !policy.is_valid()) {
+            problems.push(RulesetProblem::InvalidDealerPolicy { policy });
+        }
+        if let Some(cards) = self.multi_card_double.filter(|&cards| cards < 3) {
+            problems.push(RulesetProblem::MultiCardDoubleTooFewCards { cards });
+        }
+        match problems.is_empty() {
+            true => Ok(()),
+            false => Err(problems),
+        }
+    }
+
+    // Resolves settings implied by other settings, eg. aces cannot be split
+    // more often than the hand limit allows. The payout is left untouched.
+    pub fn normalize(&self) -> BlackjackRuleset {
+        let mut rules = *self;
+        let max_ace_splits = rules.max_hands_after_split.max_hands() - 1;
+        if rules.split_aces.max_splits() > max_ace_splits {
+            rules.split_aces = SplitAces::from_max_splits(max_ace_splits);
+        }
+        if matches!(rules.split_aces, SplitAces::NotAllowed) {
+            rules.hit_on_split_ace = false;
+            rules.double_on_split_ace = false;
+            rules.split_ace_can_be_blackjack = false;
+        }
+        if !rules.double_after_split {
+            rules.double_on_split_ace = false;
+        }
+        rules.charlie = rules.charlie.map(|cards| cards.max(3));
+        if let Some(policy) = &mut rules.dealer_policy {
+            policy.stands_on_soft = policy.stands_on_soft.clamp(12, 21);
+            policy.stands_on_hard = policy.stands_on_hard.clamp(12, 21);
+            rules.dealer_stands_on_all_17 = policy.stands_on(HandValue::Soft(17));
+        }
+        // doubling on two cards is the default
+        rules.multi_card_double = rules.multi_card_double.filter(|&cards| cards >= 3);
+        rules
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackjack::presets::PRESETS;
+
+    #[test]
+    fn test_presets_are_valid() {
+        for preset in PRESETS.iter() {
+            assert_eq!(preset.rules.validate(), Ok(()), "{}", preset.name);
+        }
+    }
+
+    #[test]
+    fn test_dealer_peeks_accepts_a_bool() {
+        let rules = serde_json::to_value(PRESETS[0].rules).unwrap();
+        assert_eq!(rules["dealer_peeks"], "Both");
+        for (setting, policy) in [
+            (serde_json::json!(true), PeekPolicy::Both),
+            (serde_json::json!(false), PeekPolicy::None),
+            (serde_json::json!("TenOnly"), PeekPolicy::TenOnly),
+        ] {
+            let mut rules = rules.clone();
+            rules["dealer_peeks"] = setting;
+            let rules: BlackjackRuleset = serde_json::from_value(rules).unwrap();
+            assert_eq!(rules.dealer_peeks, policy);
+        }
+    }
+
+    #[test]
+    fn test_normalize_resolves_contradictions() {
+        let rules = BlackjackRuleset {
+            split_aces: SplitAces::Thrice,
+            max_hands_after_split: MaxHandsAfterSplit::Two,
+            double_after_split: false,
+            double_on_split_ace: true,
+            charlie: Some(2),
+            multi_card_double: Some(2),
+            dealer_policy: Some(DealerPolicy {
+                stands_on_soft: 18,
+                stands_on_hard: 26,
+                push_22: false,
+            }),
+            ..PRESETS[0].rules
+        };
+        assert_eq!(rules.validate().unwrap_err().len(), 5);
+        let rules = rules.normalize();
+        assert!(matches!(rules.split_aces, SplitAces::Once));
+        assert!(!rules.double_on_split_ace);
+        assert_eq!(rules.charlie, Some(3));
+        assert_eq!(rules.multi_card_double, None);
+        assert_eq!(
+            rules.effective_dealer_policy(),
+            DealerPolicy {
+                stands_on_hard: 21,
+                ..DealerPolicy::HITS_SOFT_17
+            }
+        );
+        assert!(!rules.dealer_stands_on_all_17);
+        assert_eq!(rules.validate(), Ok(()));
+    }
 }
@@ -0,0 +1,233 @@
+// Scripted deals for setting up specific situations (eg. A-A against a ten).
+// Cards are dealt in casino order: player, dealer up card, player, dealer hole
+// card, followed by `draws` for hits, doubles, splits and the dealer's turn.
+// In Blackjack Switch the player cards are the first hand's.
+// Anything not scripted comes from a shuffled shoe with the scripted cards
+// taken out.
+//
+// As text, a scenario is written as player cards, dealer cards (up card then
+// hole card) and draws separated by slashes, eg. "As Ah / Tc 7d / 9c Kh".
+use crate::blackjack::{
+    init_shoe, init_state_with_shoe,
+    money::Money,
+    notation::{parse_cards, NotationError},
+    ruleset::{BlackjackRuleset, GameVariant, RulesetProblem},
+    BlackjackState, Card,
+};
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    #[serde(default)]
+    pub player: Vec<Card>, // at most two cards
+    #[serde(default)]
+    pub dealer_up: Option<Card>,
+    #[serde(default)]
+    pub dealer_hole: Option<Card>,
+    #[serde(default)]
+    pub draws: Vec<Card>,
+}
+
+impl Scenario {
+    pub fn new() -> Scenario {
+        Scenario::default()
+    }
+
+    pub fn player(mut self, cards: &[Card]) -> Scenario {
+        self.player = cards.to_vec();
+        self
+    }
+
+    pub fn dealer_up(mut self, card: Card) -> Scenario {
+        self.dealer_up = Some(card);
+        self
+    }
+
+    pub fn dealer_hole(mut self, card: Card) -> Scenario {
+        self.dealer_hole = Some(card);
+        self
+    }
+
+    pub fn draws(mut self, cards: &[Card]) -> Scenario {
+        self.draws = cards.to_vec();
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.player.len() > 2 {
+            return Err(format!(
+                "the player is dealt two cards, but {} were scripted",
+                self.player.len()
+            ));
+        }
+        Ok(())
+    }
+
+    // Returns the shoe with the scripted cards on top (the end of the vector)
+    pub fn shoe(&self, variant: GameVariant, num_decks: usize) -> Vec<Card> {
+        if let Err(problem) = self.validate() {
+            panic!("Invalid scenario: {}", problem);
+        }
+        let mut remainder = init_shoe(variant, num_decks);
+        let scripted = self
+            .player
+            .iter()
+            .chain(self.dealer_up.iter())
+            .chain(self.dealer_hole.iter())
+            .chain(self.draws.iter());
+        for card in scripted {
+            if let Some(i) = remainder
+                .iter()
+                .position(|c| c.rank == card.rank && c.suit == card.suit)
+            {
+                remainder.swap_remove(i);
+            }
+        }
+        remainder.shuffle(&mut thread_rng());
+
+        let mut next_card = |card: Option<&Card>| match card {
+            Some(card) => card.clone(),
+            None => remainder.pop().expect("Shoe is empty."),
+        };
+        let mut dealt = match variant.starting_hands() {
+            // the scripted cards are the first hand's, the second hand is
+            // dealt from the shoe
+            2 => vec![
+                next_card(self.player.first()),
+                next_card(None),
+                next_card(self.dealer_up.as_ref()),
+                next_card(self.player.get(1)),
+                next_card(None),
+                next_card(self.dealer_hole.as_ref()),
+            ],
+            _ => vec![
+                next_card(self.player.first()),
+                next_card(self.dealer_up.as_ref()),
+                next_card(self.player.get(1)),
+                next_card(self.dealer_hole.as_ref()),
+            ],
+        };
+        dealt.extend(self.draws.iter().cloned());
+        for card in dealt.iter_mut() {
+            card.face_down = false;
+        }
+
+        remainder.extend(dealt.into_iter().rev());
+        remainder
+    }
+
+    pub fn build(
+        &self,
+        starting_bet: Money,
+        rules: BlackjackRuleset,
+        num_decks: usize,
+    ) -> Result<BlackjackState, Vec<RulesetProblem>> {
+        init_state_with_shoe(starting_bet, rules, self.shoe(rules.variant, num_decks))
+    }
+}
+
+impl FromStr for Scenario {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<Vec<Card>> = s
+            .split('/')
+            .map(parse_cards)
+            .collect::<Result<_, NotationError>>()
+            .map_err(|err| err.to_string())?;
+        if parts.len() > 3 {
+            return Err("expected at most three parts: player / dealer / draws".to_string());
+        }
+        let empty = Vec::new();
+        let dealer = parts.get(1).unwrap_or(&empty);
+        if dealer.len() > 2 {
+            return Err(format!(
+                "the dealer is dealt two cards, but {} were scripted",
+                dealer.len()
+            ));
+        }
+        let scenario = Scenario {
+            player: parts[0].clone(),
+            dealer_up: dealer.first().cloned(),
+            dealer_hole: dealer.get(1).cloned(),
+            draws: parts.get(2).unwrap_or(&empty).clone(),
+        };
+        scenario.validate()?;
+        Ok(scenario)
+    }
+}
+
+// Shared by the variant tests: builds `scenario` under `rules` from a six
+// deck shoe and deals up to the player's first decision.
+#[cfg(test)]
+pub(crate) fn play_to_player_turn(scenario: &str, rules: BlackjackRuleset) -> BlackjackState {
+    let scenario: Scenario = scenario.parse().unwrap();
+    deal_to_player_turn(scenario.build(Money::from_dollars(1), rules, 6).unwrap())
+}
+
+// `play_to_player_turn` with the rules of the named preset
+#[cfg(test)]
+pub(crate) fn play_preset(name: &str, scenario: &str) -> BlackjackState {
+    let rules = crate::blackjack::presets::find_preset(name).unwrap().rules;
+    play_to_player_turn(scenario, rules)
+}
+
+#[cfg(test)]
+pub(crate) fn deal_to_player_turn(mut game: BlackjackState) -> BlackjackState {
+    while matches!(game.state, crate::blackjack::GameState::Dealing) {
+        game.next_state(None);
+    }
+    game
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackjack::{presets::PRESETS, GameState, PlayerAction, Rank, Suit};
+
+    fn card(rank: Rank, suit: Suit) -> Card {
+        Card {
+            suit,
+            rank,
+            face_down: false,
+        }
+    }
+
+    #[test]
+    fn test_aces_against_ten() {
+        let scenario = Scenario::new()
+            .player(&[card(Rank::Ace, Suit::Spades), card(Rank::Ace, Suit::Hearts)])
+            .dealer_up(card(Rank::Ten, Suit::Clubs))
+            .dealer_hole(card(Rank::Seven, Suit::Clubs))
+            .draws(&[card(Rank::Nine, Suit::Clubs), card(Rank::King, Suit::Clubs)]);
+        let parsed: Scenario = "As Ah / Tc 7c / 9c Kc".parse().unwrap();
+        assert_eq!(parsed.dealer_hole.unwrap().rank, Rank::Seven);
+        assert_eq!(parsed.draws.len(), 2);
+        assert!("As Ah 2c".parse::<Scenario>().is_err());
+        let mut game = scenario
+            .build(Money::from_dollars(1), PRESETS[0].rules, 1)
+            .unwrap();
+        assert_eq!(game.shoe.len(), 52);
+        while matches!(game.state, GameState::Dealing) {
+            game.next_state(None);
+        }
+        assert!(matches!(game.state, GameState::PlayerTurn));
+        assert!(game.player_hands[0]
+            .cards
+            .iter()
+            .all(|c| c.rank == Rank::Ace));
+        assert_eq!(game.dealer_hand[0].rank, Rank::Ten);
+        assert_eq!(game.get_optimal_move(), PlayerAction::Split);
+
+        game.next_state(Some(PlayerAction::Split));
+        game.next_state(None);
+        assert_eq!(game.player_hands[0].cards[1].rank, Rank::Nine);
+        // both aces are gone from the one deck shoe
+        let aces = game.shoe.iter().filter(|c| c.rank == Rank::Ace).count();
+        assert_eq!(aces, 2);
+    }
+}
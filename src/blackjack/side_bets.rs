@@ -0,0 +1,468 @@
+// Side bets are placed before the deal and settled as soon as the initial
+// cards are out, independently of how the main hand plays. 21+3 is a poker
+// hand made from the player's two cards and the dealer's up card; Perfect
+// Pairs pays when the player's first two cards are a pair.
+//
+// Buster bets are the exception: they pay on the number of cards the dealer
+// busts with, so they are settled when the round is over and the dealer plays
+// out the hand even if every player hand has busted.
+use crate::blackjack::{
+    card_value,
+    events::GameEvent,
+    money::{Money, Payout},
+    BlackjackState, Card, GameState, Rank, Suit,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum SideBetKind {
+    TwentyOnePlusThree,
+    PerfectPairs,
+    Buster,
+}
+impl fmt::Display for SideBetKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            SideBetKind::TwentyOnePlusThree => "21+3",
+            SideBetKind::PerfectPairs => "Perfect Pairs",
+            SideBetKind::Buster => "Buster",
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum SideBetHand {
+    // 21+3
+    SuitedThreeOfAKind,
+    StraightFlush,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    // Perfect Pairs
+    PerfectPair,
+    ColoredPair,
+    MixedPair,
+    // Buster, by the number of cards in the dealer's busted hand
+    DealerBustThreeCards,
+    DealerBustFourCards,
+    DealerBustFiveCards,
+    DealerBustSixCards,
+    DealerBustSevenCards,
+    DealerBustEightOrMoreCards,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct PaytableEntry {
+    pub hand: SideBetHand,
+    pub payout: Payout,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "kind")]
+pub enum SideBetOutcome {
+    Won { hand: SideBetHand, payout: Payout },
+    Lost,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SideBet {
+    pub kind: SideBetKind,
+    pub amount: Money,
+    // an empty paytable uses the kind's default paytable
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paytable: Vec<PaytableEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<SideBetOutcome>,
+}
+
+const fn entry(hand: SideBetHand, numerator: u32) -> PaytableEntry {
+    PaytableEntry {
+        hand,
+        payout: Payout::new(numerator, 1),
+    }
+}
+
+pub const TWENTY_ONE_PLUS_THREE_PAYTABLE: [PaytableEntry; 5] = [
+    entry(SideBetHand::SuitedThreeOfAKind, 100),
+    entry(SideBetHand::StraightFlush, 40),
+    entry(SideBetHand::ThreeOfAKind, 30),
+    entry(SideBetHand::Straight, 10),
+    entry(SideBetHand::Flush, 5),
+];
+
+pub const PERFECT_PAIRS_PAYTABLE: [PaytableEntry; 3] = [
+    entry(SideBetHand::PerfectPair, 25),
+    entry(SideBetHand::ColoredPair, 12),
+    entry(SideBetHand::MixedPair, 6),
+];
+
+pub const BUSTER_PAYTABLE: [PaytableEntry; 6] = [
+    entry(SideBetHand::DealerBustEightOrMoreCards, 250),
+    entry(SideBetHand::DealerBustSevenCards, 100),
+    entry(SideBetHand::DealerBustSixCards, 50),
+    entry(SideBetHand::DealerBustFiveCards, 9),
+    entry(SideBetHand::DealerBustFourCards, 2),
+    entry(SideBetHand::DealerBustThreeCards, 1),
+];
+
+impl SideBetKind {
+    pub fn default_paytable(&self) -> &'static [PaytableEntry] {
+        match self {
+            SideBetKind::TwentyOnePlusThree => &TWENTY_ONE_PLUS_THREE_PAYTABLE,
+            SideBetKind::PerfectPairs => &PERFECT_PAIRS_PAYTABLE,
+            SideBetKind::Buster => &BUSTER_PAYTABLE,
+        }
+    }
+
+    // Settled on the dealer's final hand rather than the initial deal
+    pub fn settles_on_dealer_hand(&self) -> bool {
+        matches!(self, SideBetKind::Buster)
+    }
+
+    fn pays_on(&self, hand: SideBetHand) -> bool {
+        self.default_paytable()
+            .iter()
+            .any(|entry| entry.hand == hand)
+    }
+
+    // Every hand the cards qualify for, best first. Suited trips also count
+    // as trips and a flush, so a paytable without them still pays.
+    fn qualifying_hands(&self, cards: &[Card]) -> Vec<SideBetHand> {
+        match self {
+            SideBetKind::TwentyOnePlusThree => {
+                let suited = cards.iter().all(|card| card.suit == cards[0].suit);
+                let trips = cards.iter().all(|card| card.rank == cards[0].rank);
+                let straight = is_straight(cards);
+                let mut hands = Vec::new();
+                if trips && suited {
+                    hands.push(SideBetHand::SuitedThreeOfAKind);
+                }
+                if straight && suited {
+                    hands.push(SideBetHand::StraightFlush);
+                }
+                if trips {
+                    hands.push(SideBetHand::ThreeOfAKind);
+                }
+                if straight {
+                    hands.push(SideBetHand::Straight);
+                }
+                if suited {
+                    hands.push(SideBetHand::Flush);
+                }
+                hands
+            }
+            SideBetKind::PerfectPairs => {
+                let (card1, card2) = (&cards[0], &cards[1]);
+                if card1.rank != card2.rank {
+                    Vec::new()
+                } else if card1.suit == card2.suit {
+                    vec![
+                        SideBetHand::PerfectPair,
+                        SideBetHand::ColoredPair,
+                        SideBetHand::MixedPair,
+                    ]
+                } else if is_red(card1.suit) == is_red(card2.suit) {
+                    vec![SideBetHand::ColoredPair, SideBetHand::MixedPair]
+                } else {
+                    vec![SideBetHand::MixedPair]
+                }
+            }
+            SideBetKind::Buster => {
+                // aces all count as one once the hand is over 21
+                let total: u32 = cards
+                    .iter()
+                    .map(|card| {
+                        let card = Card {
+                            face_down: false,
+                            ..card.clone()
+                        };
+                        card_value(&card, false) as u32
+                    })
+                    .sum();
+                if total <= 21 {
+                    return Vec::new();
+                }
+                vec![match cards.len() {
+                    0..=3 => SideBetHand::DealerBustThreeCards,
+                    4 => SideBetHand::DealerBustFourCards,
+                    5 => SideBetHand::DealerBustFiveCards,
+                    6 => SideBetHand::DealerBustSixCards,
+                    7 => SideBetHand::DealerBustSevenCards,
+                    _ => SideBetHand::DealerBustEightOrMoreCards,
+                }]
+            }
+        }
+    }
+}
+
+fn is_red(suit: Suit) -> bool {
+    matches!(suit, Suit::Hearts | Suit::Diamonds)
+}
+
+fn is_straight(cards: &[Card]) -> bool {
+    // ranks are declared from Two up to Ace, so the discriminant is the order
+    let mut ranks: Vec<u8> = cards.iter().map(|card| card.rank as u8).collect();
+    ranks.sort_unstable();
+    let consecutive = ranks.windows(2).all(|pair| pair[1] == pair[0] + 1);
+    let ace_low = ranks == [Rank::Two as u8, Rank::Three as u8, Rank::Ace as u8];
+    consecutive || ace_low
+}
+
+impl SideBet {
+    pub fn new(kind: SideBetKind, amount: Money) -> SideBet {
+        SideBet {
+            kind,
+            amount,
+            paytable: Vec::new(),
+            outcome: None,
+        }
+    }
+
+    pub fn paytable(&self) -> &[PaytableEntry] {
+        match self.paytable.is_empty() {
+            true => self.kind.default_paytable(),
+            false => &self.paytable,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.amount <= Money::ZERO {
+            return Err(format!(
+                "{} amount must be positive (got {})",
+                self.kind, self.amount
+            ));
+        }
+        if let Some(entry) = self
+            .paytable
+            .iter()
+            .find(|entry| !self.kind.pays_on(entry.hand))
+        {
+            return Err(format!("{} does not pay on {:?}", self.kind, entry.hand));
+        }
+        Ok(())
+    }
+
+    // `cards` are the player's first two cards followed by the dealer's up
+    // card, or the dealer's final hand for bets settled on the dealer's hand
+    pub fn settle(&self, cards: &[Card]) -> SideBetOutcome {
+        let paytable = self.paytable();
+        self.kind
+            .qualifying_hands(cards)
+            .into_iter()
+            .find_map(|hand| {
+                paytable
+                    .iter()
+                    .find(|entry| entry.hand == hand)
+                    .map(|entry| SideBetOutcome::Won {
+                        hand,
+                        payout: entry.payout,
+                    })
+            })
+            .unwrap_or(SideBetOutcome::Lost)
+    }
+
+    // Winnings, or minus the amount if the bet lost; zero until settled
+    pub fn net(&self) -> Money {
+        match self.outcome {
+            Some(SideBetOutcome::Won { payout, .. }) => self.amount.times(payout),
+            Some(SideBetOutcome::Lost) => -self.amount,
+            None => Money::ZERO,
+        }
+    }
+}
+
+impl BlackjackState {
+    pub fn place_side_bet(&mut self, side_bet: SideBet) -> Result<(), String> {
+        if !matches!(self.state, GameState::Dealing) || !self.dealer_hand.is_empty() {
+            return Err("Side bets must be placed before the first card is dealt.".to_string());
+        }
+        side_bet
+            .validate()
+            .map_err(|problem| format!("Invalid side bet: {}", problem))?;
+        self.side_bets.push(SideBet {
+            outcome: None,
+            ..side_bet
+        });
+        Ok(())
+    }
+
+    // The dealer draws to a finished hand even when no player hand is left
+    // in play, so bets on the dealer's hand can be settled
+    pub(super) fn dealer_must_play_out(&self) -> bool {
+        self.side_bets
+            .iter()
+            .any(|side_bet| side_bet.kind.settles_on_dealer_hand())
+    }
+
+    // Called once the initial cards are dealt
+    pub(super) fn settle_side_bets(&mut self) {
+        if self.side_bets.is_empty() {
+            return;
+        }
+        let mut cards = self.player_hands[0].cards[..2].to_vec();
+        cards.push(self.dealer_hand[0].clone());
+        self.settle_side_bets_where(&cards, false);
+    }
+
+    // Called when the round is over
+    pub(super) fn settle_dealer_side_bets(&mut self) {
+        if self.dealer_must_play_out() {
+            let cards = self.dealer_hand.clone();
+            self.settle_side_bets_where(&cards, true);
+        }
+    }
+
+    fn settle_side_bets_where(&mut self, cards: &[Card], on_dealer_hand: bool) {
+        for index in 0..self.side_bets.len() {
+            if self.side_bets[index].kind.settles_on_dealer_hand() != on_dealer_hand {
+                continue;
+            }
+            let outcome = self.side_bets[index].settle(cards);
+            self.side_bets[index].outcome = Some(outcome);
+            self.emit(GameEvent::SideBetSettled {
+                bet: self.side_bets[index].kind,
+                outcome,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackjack::{notation::parse_cards, presets::PRESETS, scenario::Scenario};
+
+    fn settle(kind: SideBetKind, cards: &str) -> SideBetOutcome {
+        SideBet::new(kind, Money::from_dollars(1)).settle(&parse_cards(cards).unwrap())
+    }
+
+    #[test]
+    fn test_twenty_one_plus_three_hands() {
+        use SideBetKind::TwentyOnePlusThree;
+        let won = |hand| SideBetOutcome::Won {
+            hand,
+            payout: TwentyOnePlusThree
+                .default_paytable()
+                .iter()
+                .find(|entry| entry.hand == hand)
+                .unwrap()
+                .payout,
+        };
+        assert_eq!(
+            settle(TwentyOnePlusThree, "7h 7h 7h"),
+            won(SideBetHand::SuitedThreeOfAKind)
+        );
+        assert_eq!(
+            settle(TwentyOnePlusThree, "Qs Ks As"),
+            won(SideBetHand::StraightFlush)
+        );
+        assert_eq!(
+            settle(TwentyOnePlusThree, "7h 7d 7c"),
+            won(SideBetHand::ThreeOfAKind)
+        );
+        assert_eq!(
+            settle(TwentyOnePlusThree, "Ah 2d 3c"),
+            won(SideBetHand::Straight)
+        );
+        assert_eq!(
+            settle(TwentyOnePlusThree, "2h 9h Kh"),
+            won(SideBetHand::Flush)
+        );
+        assert_eq!(settle(TwentyOnePlusThree, "Kh Ad 2c"), SideBetOutcome::Lost);
+    }
+
+    #[test]
+    fn test_perfect_pairs_tiers_and_custom_paytable() {
+        use SideBetKind::PerfectPairs;
+        let pays = |cards| match settle(PerfectPairs, cards) {
+            SideBetOutcome::Won { hand, .. } => Some(hand),
+            SideBetOutcome::Lost => None,
+        };
+        assert_eq!(pays("8h 8h 2c"), Some(SideBetHand::PerfectPair));
+        assert_eq!(pays("8h 8d 2c"), Some(SideBetHand::ColoredPair));
+        assert_eq!(pays("8h 8s 2c"), Some(SideBetHand::MixedPair));
+        assert_eq!(pays("8h 9h 2c"), None);
+
+        // without a colored pair entry, a colored pair pays as a mixed pair
+        let mut side_bet = SideBet::new(PerfectPairs, Money::from_dollars(2));
+        side_bet.paytable = vec![
+            entry(SideBetHand::PerfectPair, 30),
+            entry(SideBetHand::MixedPair, 5),
+        ];
+        assert!(side_bet.validate().is_ok());
+        side_bet.outcome = Some(side_bet.settle(&parse_cards("8h 8d 2c").unwrap()));
+        assert_eq!(side_bet.net(), Money::from_dollars(10));
+
+        side_bet.paytable.push(entry(SideBetHand::Flush, 5));
+        assert!(side_bet.validate().is_err());
+    }
+
+    #[test]
+    fn test_side_bets_settle_after_the_deal() {
+        let scenario: Scenario = "8h 8d / 9h 7c".parse().unwrap();
+        let mut game = scenario
+            .build(Money::from_dollars(1), PRESETS[0].rules, 1)
+            .unwrap();
+        game.place_side_bet(SideBet::new(
+            SideBetKind::PerfectPairs,
+            Money::from_dollars(1),
+        ))
+        .unwrap();
+        game.place_side_bet(SideBet::new(
+            SideBetKind::TwentyOnePlusThree,
+            Money::from_dollars(1),
+        ))
+        .unwrap();
+        while matches!(game.state, GameState::Dealing) {
+            game.next_state(None);
+        }
+        let nets: Vec<Money> = game
+            .side_bets
+            .iter()
+            .map(|side_bet| side_bet.net())
+            .collect();
+        assert_eq!(nets, vec![Money::from_dollars(12), Money::from_dollars(-1)]);
+        assert!(game
+            .place_side_bet(SideBet::new(SideBetKind::Buster, Money::from_dollars(1)))
+            .is_err());
+    }
+
+    #[test]
+    fn test_buster_is_settled_after_the_player_busts() {
+        use crate::blackjack::{HandOutcome, LossReason, PlayerAction};
+        assert_eq!(
+            settle(SideBetKind::Buster, "Tc 6d As 5h"),
+            SideBetOutcome::Won {
+                hand: SideBetHand::DealerBustFourCards,
+                payout: Payout::new(2, 1),
+            }
+        );
+        assert_eq!(
+            settle(SideBetKind::Buster, "Tc 6d 5s"),
+            SideBetOutcome::Lost
+        );
+
+        let scenario: Scenario = "Th 6d / 6c Tc / 9s 8d".parse().unwrap();
+        let mut game = scenario
+            .build(Money::from_dollars(1), PRESETS[0].rules, 1)
+            .unwrap();
+        game.place_side_bet(SideBet::new(SideBetKind::Buster, Money::from_dollars(1)))
+            .unwrap();
+        while matches!(game.state, GameState::Dealing) {
+            game.next_state(None);
+        }
+        game.next_state(Some(PlayerAction::Hit));
+        assert!(matches!(game.state, GameState::DealerTurn));
+        assert_eq!(game.side_bets[0].outcome, None);
+        while !matches!(game.state, GameState::GameOver) {
+            game.next_state(None);
+        }
+        assert_eq!(game.dealer_hand.len(), 3);
+        assert_eq!(
+            game.player_hand_outcomes(),
+            vec![HandOutcome::Lost(LossReason::Bust)]
+        );
+        assert_eq!(game.side_bets[0].net(), Money::from_dollars(1));
+    }
+}
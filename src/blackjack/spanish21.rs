@@ -0,0 +1,203 @@
+// Spanish 21 bonuses. A player 21 always wins in Spanish 21, and some 21s pay
+// a bonus instead of even money: 21 made with five or more cards, and 6-7-8
+// or 7-7-7, which pay more when suited or all spades. Bonuses are not paid on
+// doubled hands.
+use crate::blackjack::{card_value, money::Payout, Card, Rank, Suit};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// a hand may be doubled, then redoubled twice more
+pub const MAX_REDOUBLES: u8 = 2;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum BonusSuits {
+    Mixed,
+    Suited,
+    Spades,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum BonusHand {
+    FiveCardTwentyOne,
+    SixCardTwentyOne,
+    SevenCardTwentyOne, // seven or more cards
+    SixSevenEight(BonusSuits),
+    SevenSevenSeven(BonusSuits),
+}
+impl fmt::Display for BonusHand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let suits = |suits: &BonusSuits| match suits {
+            BonusSuits::Mixed => "",
+            BonusSuits::Suited => " suited",
+            BonusSuits::Spades => " in spades",
+        };
+        match self {
+            BonusHand::FiveCardTwentyOne => f.write_str("five card 21"),
+            BonusHand::SixCardTwentyOne => f.write_str("six card 21"),
+            BonusHand::SevenCardTwentyOne => f.write_str("seven card 21"),
+            BonusHand::SixSevenEight(s) => write!(f, "6-7-8{}", suits(s)),
+            BonusHand::SevenSevenSeven(s) => write!(f, "7-7-7{}", suits(s)),
+        }
+    }
+}
+
+impl BonusHand {
+    pub fn payout(&self) -> Payout {
+        match self {
+            BonusHand::FiveCardTwentyOne => Payout::THREE_TO_TWO,
+            BonusHand::SixCardTwentyOne => Payout::new(2, 1),
+            BonusHand::SevenCardTwentyOne => Payout::new(3, 1),
+            BonusHand::SixSevenEight(suits) | BonusHand::SevenSevenSeven(suits) => match suits {
+                BonusSuits::Mixed => Payout::THREE_TO_TWO,
+                BonusSuits::Suited => Payout::new(2, 1),
+                BonusSuits::Spades => Payout::new(3, 1),
+            },
+        }
+    }
+
+    // The bonus a hand qualifies for, if any
+    pub fn find(cards: &[Card]) -> Option<BonusHand> {
+        let total: u8 = cards.iter().map(|card| card_value(card, false)).sum();
+        let soft_total = match cards.iter().any(|card| card.rank == Rank::Ace) {
+            true if total <= 11 => total + 10,
+            _ => total,
+        };
+        if soft_total != 21 {
+            return None;
+        }
+        if cards.len() == 3 {
+            let mut ranks: Vec<u8> = cards.iter().map(|card| card.rank as u8).collect();
+            ranks.sort_unstable();
+            let (six, seven, eight) = (Rank::Six as u8, Rank::Seven as u8, Rank::Eight as u8);
+            let suits = if cards.iter().all(|card| card.suit == Suit::Spades) {
+                BonusSuits::Spades
+            } else if cards.iter().all(|card| card.suit == cards[0].suit) {
+                BonusSuits::Suited
+            } else {
+                BonusSuits::Mixed
+            };
+            if ranks == [six, seven, eight] {
+                return Some(BonusHand::SixSevenEight(suits));
+            }
+            if ranks == [seven, seven, seven] {
+                return Some(BonusHand::SevenSevenSeven(suits));
+            }
+        }
+        match cards.len() {
+            0..=4 => None,
+            5 => Some(BonusHand::FiveCardTwentyOne),
+            6 => Some(BonusHand::SixCardTwentyOne),
+            _ => Some(BonusHand::SevenCardTwentyOne),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackjack::{
+        constants::SPANISH_DECK, money::Money, notation::parse_cards, scenario, BlackjackState,
+        GameState, HandOutcome, PlayerAction, WinReason,
+    };
+
+    fn play_out(game: &mut BlackjackState) {
+        while !matches!(game.state, GameState::GameOver) {
+            game.next_state(None);
+        }
+    }
+
+    #[test]
+    fn test_bonus_hands() {
+        let find = |cards| BonusHand::find(&parse_cards(cards).unwrap());
+        assert_eq!(
+            find("6s 7s 8s"),
+            Some(BonusHand::SixSevenEight(BonusSuits::Spades))
+        );
+        assert_eq!(
+            find("7h 7d 7h"),
+            Some(BonusHand::SevenSevenSeven(BonusSuits::Mixed))
+        );
+        assert_eq!(find("2c 3d 4h 5s 7c"), Some(BonusHand::FiveCardTwentyOne));
+        assert_eq!(find("As 5c 5d"), None);
+        assert_eq!(find("2c 3d 4h 5s 6c"), None);
+        assert_eq!(SPANISH_DECK.len(), 48);
+        assert!(SPANISH_DECK.iter().all(|card| card.rank != Rank::Ten));
+    }
+
+    #[test]
+    fn test_strategy_differs_from_standard_play() {
+        // without tens, 9 and soft 17 are hit against a 3 rather than doubled
+        let optimal_move = |cards| scenario::play_preset("spanish-21", cards).get_optimal_move();
+        assert_eq!(optimal_move("5s 4d / 3c 7h"), PlayerAction::Hit); // 9 vs 3
+        assert_eq!(optimal_move("As 6d / 3c 7h"), PlayerAction::Hit); // soft 17 vs 3
+    }
+
+    #[test]
+    fn test_redouble_and_surrender_after_doubling() {
+        let mut game = scenario::play_preset("spanish-21", "5s 4d / Kc 7h / 2c Jd");
+        game.next_state(Some(PlayerAction::DoubleDown));
+        assert!(matches!(game.state, GameState::PlayerTurn));
+        assert_eq!(
+            game.allowed_actions(),
+            vec![
+                PlayerAction::Stand,
+                PlayerAction::DoubleDown,
+                PlayerAction::Surrender
+            ]
+        );
+        game.next_state(Some(PlayerAction::DoubleDown));
+        play_out(&mut game);
+        assert_eq!(game.bets, vec![Money::from_dollars(4)]);
+        assert_eq!(
+            game.player_hand_outcomes(),
+            vec![HandOutcome::Won(WinReason::HigherHand)]
+        );
+
+        let mut game = scenario::play_preset("spanish-21", "5s 4d / Kc 7h / 3c");
+        game.next_state(Some(PlayerAction::DoubleDown));
+        game.next_state(Some(PlayerAction::Surrender));
+        let outcome = game.player_hand_outcomes()[0];
+        assert_eq!(outcome, HandOutcome::Surrendered);
+        assert_eq!(
+            outcome.net(game.bets[0], Money::ZERO, &game.rules),
+            Money::from_dollars(-1)
+        );
+
+        // surrendering after a redouble still only costs the original bet
+        let mut game = scenario::play_preset("spanish-21", "5s 4d / Kc 7h / 2c 2d");
+        game.next_state(Some(PlayerAction::DoubleDown));
+        game.next_state(Some(PlayerAction::DoubleDown));
+        assert!(matches!(game.state, GameState::PlayerTurn));
+        game.next_state(Some(PlayerAction::Surrender));
+        assert_eq!(
+            game.player_hand_outcomes()[0].net(game.bets[0], Money::ZERO, &game.rules),
+            Money::from_dollars(-1)
+        );
+    }
+
+    #[test]
+    fn test_player_twenty_one_always_wins() {
+        let mut game = scenario::play_preset("spanish-21", "Kc 6d / Qs 6h / 5c 5d");
+        game.next_state(Some(PlayerAction::Hit));
+        play_out(&mut game);
+        assert_eq!(
+            game.player_hand_outcomes(),
+            vec![HandOutcome::Won(WinReason::TwentyOne)]
+        );
+
+        let mut game = scenario::play_preset("spanish-21", "6s 7s / Qh 8h / 8s");
+        game.next_state(Some(PlayerAction::Hit));
+        play_out(&mut game);
+        let outcome = game.player_hand_outcomes()[0];
+        assert_eq!(
+            outcome,
+            HandOutcome::Won(WinReason::Bonus(BonusHand::SixSevenEight(
+                BonusSuits::Spades
+            )))
+        );
+        assert_eq!(
+            outcome.net(game.bets[0], Money::ZERO, &game.rules),
+            Money::from_dollars(3)
+        );
+    }
+}
@@ -0,0 +1,173 @@
+// Blackjack Switch: the player plays two hands of equal bets and, before
+// playing them, may swap the second cards of the two hands. A blackjack made
+// by switching only counts as 21. To pay for the switch, a blackjack pays
+// even money (set by the ruleset) and a dealer 22 pushes every hand that
+// isn't a blackjack or already busted.
+use crate::blackjack::{
+    card_value,
+    constants::basic_strategy_tables::switch::{HARD, SOFT},
+    hand_ev::PlayedHand,
+    ruleset::SplitAces,
+    BlackjackState, Card, CardRecipient, Hand, HandValue, OutOfCards,
+};
+
+impl BlackjackState {
+    // Deals the opening cards: one to each hand, the dealer's up card, a
+    // second card to each hand and the hole card
+    pub(super) fn deal_switch_card(&mut self) -> Result<(), OutOfCards> {
+        let recipient = match (
+            self.dealer_hand.len(),
+            self.player_hands[0].cards.len(),
+            self.player_hands[1].cards.len(),
+        ) {
+            (0, 0, 0) | (1, 1, 1) => CardRecipient::Player { hand_index: 0 },
+            (0, 1, 0) | (1, 2, 1) => CardRecipient::Player { hand_index: 1 },
+            (0, 1, 1) => CardRecipient::Dealer,
+            (1, 2, 2) => return self.deal_hole_card(),
+            _ => panic!("Unreachable code: {:?}", self),
+        };
+        let card = self.draw()?;
+        match recipient {
+            CardRecipient::Player { hand_index } => self.player_hands[hand_index].cards.push(card),
+            CardRecipient::Dealer => self.dealer_hand.push(card),
+        }
+        self.emit_card_dealt(recipient);
+        Ok(())
+    }
+
+    // Swaps the second cards of the two hands
+    pub(super) fn switch_hands(&mut self) {
+        let (first, second) = self.player_hands.split_at_mut(1);
+        std::mem::swap(&mut first[0].cards[1], &mut second[0].cards[1]);
+        for hand in self.player_hands.iter_mut() {
+            hand.switched = true;
+        }
+    }
+
+    // What a two card hand is worth played by the Switch tables against the
+    // dealer's up card, once the dealer has checked for a blackjack
+    pub(super) fn switch_hand_ev(&self, played: &mut PlayedHand, hand: &Hand) -> f64 {
+        match self.player_hand_value(hand) {
+            HandValue::Blackjack => self.rules.blackjack_payout.as_f64(),
+            _ if hand.is_pair_of_aces() && self.rules.split_aces != SplitAces::NotAllowed => {
+                played.split_aces_ev(self.rules.hit_on_split_ace)
+            }
+            HandValue::Hard(n) => played.ev(n, false, 2),
+            HandValue::Soft(n) => played.ev(n, true, 2),
+        }
+    }
+
+    // Switches when the two switched hands are together worth more than the
+    // two dealt hands
+    pub fn should_switch(&self) -> bool {
+        let column = card_value(&self.dealer_hand[0], true) as usize - 2;
+        let strategy = |total: u8, soft: bool| match soft {
+            true => &SOFT[total as usize - 12][column],
+            false => &HARD[total.max(8) as usize - 8][column],
+        };
+        let mut played = PlayedHand::new(&self.rules, self.dealer_outcomes(), &strategy);
+        let mut hand_ev = |first: &Card, second: &Card, switched: bool| {
+            let mut hand = Hand {
+                switched,
+                ..Hand::new()
+            };
+            hand.cards.extend([first.clone(), second.clone()]);
+            self.switch_hand_ev(&mut played, &hand)
+        };
+        let (first, second) = (&self.player_hands[0].cards, &self.player_hands[1].cards);
+        let dealt = hand_ev(&first[0], &first[1], false) + hand_ev(&second[0], &second[1], false);
+        let switched = hand_ev(&first[0], &second[1], true) + hand_ev(&second[0], &first[1], true);
+        switched > dealt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blackjack::{
+        init_shoe, init_state_with_shoe, money::Money, notation::parse_cards, presets::find_preset,
+        ruleset::GameVariant, scenario::deal_to_player_turn, BlackjackState, GameState, HandValue,
+        PlayerAction,
+    };
+    use crate::monte_carlo::{play_optimal_round, round_net};
+
+    // `cards` are in the order they are dealt: first hand, second hand, up
+    // card, first hand, second hand, hole card, then draws
+    fn deal_to_switch_decision(cards: &str) -> BlackjackState {
+        let rules = find_preset("switch").unwrap().rules;
+        let mut shoe = init_shoe(GameVariant::Switch, 6);
+        shoe.extend(parse_cards(cards).unwrap().into_iter().rev());
+        deal_to_player_turn(init_state_with_shoe(Money::from_dollars(1), rules, shoe).unwrap())
+    }
+
+    #[test]
+    fn test_switch_and_dealer_22_push() {
+        // T-5 and 6-K against a 6 switch to T-K and 6-5
+        let mut game = deal_to_switch_decision("Ts 6h 6c 5d Kd 9s Ks 7h");
+        assert_eq!(game.bets, vec![Money::from_dollars(1); 2]);
+        assert!(matches!(game.state, GameState::SwitchDecision));
+        assert_eq!(game.get_optimal_move(), PlayerAction::Switch);
+        game.next_state(Some(PlayerAction::Switch));
+        assert!(matches!(game.state, GameState::PlayerTurn));
+        assert_eq!(
+            game.player_hand_value(&game.player_hands[0]),
+            HandValue::Hard(20)
+        );
+        assert_eq!(
+            game.player_hand_value(&game.player_hands[1]),
+            HandValue::Hard(11)
+        );
+        assert!(!game.allowed_actions().contains(&PlayerAction::Switch));
+        play_optimal_round(&mut game);
+        // the 11 doubles to 21, the dealer makes 22 and both hands push
+        assert_eq!(
+            game.bets,
+            vec![Money::from_dollars(1), Money::from_dollars(2)]
+        );
+        assert_eq!(round_net(&game), Money::ZERO);
+    }
+
+    #[test]
+    fn test_switched_blackjack_counts_as_21() {
+        let mut game = deal_to_switch_decision("As 5h 7c 9d Kd Ts");
+        game.next_state(Some(PlayerAction::Switch));
+        assert_eq!(
+            game.player_hand_value(&game.player_hands[0]),
+            HandValue::Hard(21)
+        );
+        // the 21 is finished, play moves on to the second hand
+        game.next_state(None);
+        assert!(matches!(game.state, GameState::PlayerTurn));
+        assert_eq!(game.hand_index, 1);
+
+        // a dealt blackjack may still be broken up by switching
+        let game = deal_to_switch_decision("As 5h 7c Kd 9d Ts");
+        assert_eq!(
+            game.allowed_actions(),
+            vec![PlayerAction::Stand, PlayerAction::Switch]
+        );
+    }
+
+    #[test]
+    fn test_standing_keeps_the_dealt_hands() {
+        // T-9 and 7-8 against a 6 are kept as dealt
+        let mut game = deal_to_switch_decision("Ts 7h 6c 9d 8s Kd");
+        game.enable_undo();
+        assert_eq!(game.get_optimal_move(), PlayerAction::Stand);
+        game.next_state(Some(PlayerAction::Stand));
+        assert!(matches!(game.state, GameState::PlayerTurn));
+        assert_eq!(
+            game.player_hand_value(&game.player_hands[0]),
+            HandValue::Hard(19)
+        );
+        assert!(!game.allowed_actions().contains(&PlayerAction::Switch));
+
+        // the decision can be taken back like any other
+        assert!(game.undo());
+        assert!(matches!(game.state, GameState::SwitchDecision));
+        game.next_state(Some(PlayerAction::Switch));
+        assert_eq!(
+            game.player_hand_value(&game.player_hands[0]),
+            HandValue::Hard(18)
+        );
+    }
+}
@@ -0,0 +1,130 @@
+// Undo support for training: once enabled, the game keeps a snapshot of every
+// player turn so a decision can be taken back and replayed with the same shoe.
+use crate::blackjack::{money::Money, side_bets::SideBet, BlackjackState, Card, GameState, Hand};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Snapshot {
+    pub shoe: Vec<Card>,
+    pub dealer_hand: Vec<Card>,
+    pub player_hands: Vec<Hand>,
+    pub hand_index: usize,
+    pub bets: Vec<Money>,
+    pub state: GameState,
+    // bets settled on the dealer's hand are unsettled again by an undo
+    #[serde(default)]
+    pub side_bets: Vec<SideBet>,
+}
+
+impl BlackjackState {
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            shoe: self.shoe.clone(),
+            dealer_hand: self.dealer_hand.clone(),
+            player_hands: self.player_hands.clone(),
+            hand_index: self.hand_index,
+            bets: self.bets.clone(),
+            state: self.state,
+            side_bets: self.side_bets.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.shoe = snapshot.shoe;
+        self.dealer_hand = snapshot.dealer_hand;
+        self.player_hands = snapshot.player_hands;
+        self.hand_index = snapshot.hand_index;
+        self.bets = snapshot.bets;
+        self.state = snapshot.state;
+        self.side_bets = snapshot.side_bets;
+    }
+
+    // Undo is opt-in so simulations don't pay for the snapshots
+    pub fn enable_undo(&mut self) {
+        if self.undo_stack.is_none() {
+            self.undo_stack = Some(Vec::new());
+        }
+    }
+
+    // Number of player decisions that can be taken back
+    pub fn undo_depth(&self) -> usize {
+        self.undo_stack.as_ref().map_or(0, |stack| stack.len())
+    }
+
+    // Called before a player action is applied
+    pub(super) fn push_undo_snapshot(&mut self) {
+        if self.undo_stack.is_some() && self.state.awaits_player_action() {
+            let snapshot = self.snapshot();
+            if let Some(stack) = &mut self.undo_stack {
+                stack.push(snapshot);
+            }
+        }
+    }
+
+    // Returns to the state before the last decision
+    pub fn undo(&mut self) -> bool {
+        match self.undo_depth() {
+            0 => false,
+            depth => self.rewind_to(depth - 1),
+        }
+    }
+
+    // Returns to the state before decision `depth` (0 is the first
+    // decision of the round), discarding every later decision
+    pub fn rewind_to(&mut self, depth: usize) -> bool {
+        let snapshot = match &mut self.undo_stack {
+            Some(stack) if depth < stack.len() => {
+                let snapshot = stack.swap_remove(depth);
+                stack.truncate(depth);
+                snapshot
+            }
+            _ => return false,
+        };
+        self.restore(snapshot);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blackjack::{init_state, money::Money, presets::PRESETS, GameState, PlayerAction};
+
+    #[test]
+    fn test_undo_replays_the_same_cards() {
+        let mut game = init_state(Money::from_dollars(1), PRESETS[0].rules).unwrap();
+        game.enable_undo();
+        while !matches!(game.state, GameState::PlayerTurn | GameState::GameOver) {
+            game.next_state(None);
+        }
+        if matches!(game.state, GameState::GameOver) {
+            // dealt a blackjack, there is no decision to undo
+            assert!(!game.undo());
+            return;
+        }
+        let player_hands = game.player_hands.clone();
+        let shoe_len = game.shoe.len();
+
+        game.next_state(Some(PlayerAction::Hit));
+        let hit_card = game.player_hands[0].cards.last().unwrap().rank;
+        while !matches!(game.state, GameState::GameOver) {
+            let action = match game.state {
+                GameState::PlayerTurn => Some(PlayerAction::Stand),
+                _ => None,
+            };
+            game.next_state(action);
+        }
+
+        assert!(game.rewind_to(0));
+        assert!(matches!(game.state, GameState::PlayerTurn));
+        assert_eq!(game.undo_depth(), 0);
+        assert_eq!(game.shoe.len(), shoe_len);
+        assert_eq!(
+            game.player_hands[0].cards.len(),
+            player_hands[0].cards.len()
+        );
+        game.next_state(Some(PlayerAction::Hit));
+        assert_eq!(game.player_hands[0].cards.last().unwrap().rank, hit_card);
+        assert!(game.undo());
+        assert!(!game.undo());
+    }
+}
@@ -0,0 +1,306 @@
+use blackjack_analyzer_rs::blackjack::{
+    presets::{find_preset, PRESETS},
+    scenario::Scenario,
+    side_bets::SideBet,
+};
+use blackjack_analyzer_rs::{BlackjackRuleset, Money};
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, path::Path};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    // either the name of a preset or a full ruleset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<BlackjackRuleset>,
+    #[serde(default)]
+    pub shoe: ShoeConfig,
+    #[serde(default)]
+    pub simulation: SimulationConfig,
+    // scripted deal used for every round of play
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scenario: Option<Scenario>,
+    // placed on every round of play and simulation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub side_bets: Vec<SideBet>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ShoeConfig {
+    // defaults to the preset's deck count
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_decks: Option<usize>,
+    // fraction of the shoe dealt before reshuffling; 0 reshuffles every round
+    #[serde(default)]
+    pub penetration: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(deny_unknown_fields, default)]
+pub struct SimulationConfig {
+    pub threads: usize,
+    pub duration_secs: u64,
+    pub flat_bet: Money,
+}
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            threads: 16,
+            duration_secs: 5,
+            flat_bet: Money::from_dollars(1),
+        }
+    }
+}
+
+// Config with the preset resolved and all settings checked
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub title: String,
+    pub description: String,
+    pub rules: BlackjackRuleset,
+    pub num_decks: usize,
+    pub penetration: f32,
+    pub simulation: SimulationConfig,
+    pub scenario: Option<Scenario>,
+    pub side_bets: Vec<SideBet>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    UnsupportedFormat(String),
+    Parse(String),
+    UnknownPreset(String),
+    Invalid(Vec<String>),
+}
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "Could not access config file: {}", err),
+            ConfigError::UnsupportedFormat(path) => write!(
+                f,
+                "Unsupported config file format: {} (expected .toml or .json)",
+                path
+            ),
+            ConfigError::Parse(err) => write!(f, "Could not parse config file: {}", err),
+            ConfigError::UnknownPreset(name) => write!(
+                f,
+                "Unknown ruleset preset: {}. Use --list-rules to see available presets.",
+                name
+            ),
+            ConfigError::Invalid(problems) => {
+                write!(f, "Invalid config:")?;
+                for problem in problems {
+                    write!(f, "\n  - {}", problem)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+enum Format {
+    Toml,
+    Json,
+}
+fn format_of(path: &Path) -> Result<Format, ConfigError> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("toml") => Ok(Format::Toml),
+        Some(ext) if ext.eq_ignore_ascii_case("json") => Ok(Format::Json),
+        _ => Err(ConfigError::UnsupportedFormat(path.display().to_string())),
+    }
+}
+
+pub fn load_config(path: &Path) -> Result<Config, ConfigError> {
+    let format = format_of(path)?;
+    let text = fs::read_to_string(path).map_err(ConfigError::Io)?;
+    match format {
+        Format::Toml => toml::from_str(&text).map_err(|err| ConfigError::Parse(err.to_string())),
+        Format::Json => {
+            serde_json::from_str(&text).map_err(|err| ConfigError::Parse(err.to_string()))
+        }
+    }
+}
+
+pub fn save_config(path: &Path, config: &Config) -> Result<(), ConfigError> {
+    let text = match format_of(path)? {
+        Format::Toml => {
+            toml::to_string_pretty(config).map_err(|err| ConfigError::Parse(err.to_string()))?
+        }
+        Format::Json => serde_json::to_string_pretty(config)
+            .map_err(|err| ConfigError::Parse(err.to_string()))?,
+    };
+    fs::write(path, text).map_err(ConfigError::Io)
+}
+
+impl Config {
+    pub fn resolve(&self) -> Result<Settings, ConfigError> {
+        let (title, description, rules, preset_decks) = match (&self.preset, &self.rules) {
+            (Some(_), Some(_)) => {
+                return Err(ConfigError::Invalid(vec![
+                    "specify either preset or rules, not both".to_string(),
+                ]));
+            }
+            (Some(name), None) => {
+                let preset = find_preset(name)
+                    .ok_or_else(|| ConfigError::UnknownPreset(name.to_string()))?;
+                (
+                    preset.title.to_string(),
+                    preset.description.to_string(),
+                    preset.rules,
+                    preset.num_decks,
+                )
+            }
+            (None, Some(rules)) => (
+                "Custom".to_string(),
+                "Ruleset loaded from config file.".to_string(),
+                *rules,
+                PRESETS[0].num_decks,
+            ),
+            (None, None) => (
+                PRESETS[0].title.to_string(),
+                PRESETS[0].description.to_string(),
+                PRESETS[0].rules,
+                PRESETS[0].num_decks,
+            ),
+        };
+
+        let mut problems: Vec<String> = match rules.validate() {
+            Ok(()) => Vec::new(),
+            Err(problems) => problems.iter().map(|problem| problem.to_string()).collect(),
+        };
+        let num_decks = self.shoe.num_decks.unwrap_or(preset_decks);
+        if num_decks == 0 {
+            problems.push("shoe.num_decks must be at least 1".to_string());
+        }
+        if !(0f32..1f32).contains(&self.shoe.penetration) {
+            problems.push(format!(
+                "shoe.penetration must be between 0 and 1 (got {})",
+                self.shoe.penetration
+            ));
+        }
+        if self.simulation.threads == 0 {
+            problems.push("simulation.threads must be at least 1".to_string());
+        }
+        if self.simulation.flat_bet <= Money::ZERO {
+            problems.push(format!(
+                "simulation.flat_bet must be positive (got {})",
+                self.simulation.flat_bet
+            ));
+        }
+        if let Some(Err(problem)) = self.scenario.as_ref().map(|scenario| scenario.validate()) {
+            problems.push(format!("scenario: {}", problem));
+        }
+        for side_bet in &self.side_bets {
+            if let Err(problem) = side_bet.validate() {
+                problems.push(format!("side_bets: {}", problem));
+            }
+        }
+        if !problems.is_empty() {
+            return Err(ConfigError::Invalid(problems));
+        }
+
+        Ok(Settings {
+            title,
+            description,
+            rules,
+            num_decks,
+            penetration: self.shoe.penetration,
+            simulation: self.simulation,
+            scenario: self.scenario.clone(),
+            side_bets: self.side_bets.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blackjack_analyzer_rs::blackjack::ruleset::SplitAces;
+
+    #[test]
+    fn test_toml_config_with_preset() {
+        let config: Config = toml::from_str(
+            r#"
+            preset = "downtown-vegas"
+
+            [shoe]
+            penetration = 0.75
+
+            [simulation]
+            threads = 4
+            "#,
+        )
+        .unwrap();
+        let settings = config.resolve().unwrap();
+        assert_eq!(settings.num_decks, 2);
+        assert_eq!(settings.simulation.threads, 4);
+        assert_eq!(settings.simulation.duration_secs, 5);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let config = Config {
+            rules: Some(PRESETS[1].rules),
+            ..Config::default()
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        let config: Config = serde_json::from_str(&json).unwrap();
+        assert!(config.resolve().is_ok());
+    }
+
+    #[test]
+    fn test_toml_config_with_scenario() {
+        let config: Config = toml::from_str(
+            r#"
+            [scenario]
+            player = [{ rank = "Ace", suit = "Spades" }, { rank = "Ace", suit = "Hearts" }]
+            dealer_up = { rank = "Ten", suit = "Clubs" }
+            "#,
+        )
+        .unwrap();
+        let settings = config.resolve().unwrap();
+        assert_eq!(settings.scenario.unwrap().player.len(), 2);
+    }
+
+    #[test]
+    fn test_side_bets_with_custom_paytable() {
+        let config: Config = toml::from_str(
+            r#"
+            [[side_bets]]
+            kind = "TwentyOnePlusThree"
+            amount = 1.0
+
+            [[side_bets]]
+            kind = "PerfectPairs"
+            amount = 0.5
+            paytable = [
+                { hand = "PerfectPair", payout = "30:1" },
+                { hand = "MixedPair", payout = 5 },
+            ]
+            "#,
+        )
+        .unwrap();
+        let settings = config.resolve().unwrap();
+        assert_eq!(settings.side_bets.len(), 2);
+        assert_eq!(settings.side_bets[1].paytable.len(), 2);
+    }
+
+    #[test]
+    fn test_inconsistent_rules_are_rejected() {
+        let mut rules = PRESETS[0].rules;
+        rules.split_aces = SplitAces::NotAllowed;
+        rules.hit_on_split_ace = true;
+        let config = Config {
+            rules: Some(rules),
+            ..Config::default()
+        };
+        match config.resolve() {
+            Err(ConfigError::Invalid(problems)) => assert_eq!(problems.len(), 1),
+            _ => panic!("expected an invalid config"),
+        }
+    }
+}
@@ -1,86 +1,407 @@
-use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+use serde::{de::DeserializeOwned, Serialize};
+use wasm_bindgen::{prelude::wasm_bindgen, JsError, JsValue};
 
-use crate::blackjack::{ruleset::*, BlackjackState, GameState, PlayerAction};
+use crate::blackjack::{
+    events::GameEvent,
+    house_edge,
+    money::Money,
+    notation, presets,
+    ruleset::*,
+    scenario::Scenario,
+    side_bets::{SideBet, SideBetKind},
+    BlackjackState, Card, GameState, OutOfCards, PlayerAction,
+};
+
+fn from_js<T: DeserializeOwned>(value: JsValue, what: &str) -> Result<T, JsError> {
+    serde_wasm_bindgen::from_value(value)
+        .map_err(|err| JsError::new(&format!("Invalid {}: {}", what, err)))
+}
+
+fn to_js<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, JsError> {
+    serde_wasm_bindgen::to_value(value)
+        .map_err(|err| JsError::new(&format!("Failed to serialize result: {}", err)))
+}
+
+fn ruleset_error(problems: Vec<RulesetProblem>) -> JsError {
+    let problems: Vec<String> = problems.iter().map(|problem| problem.to_string()).collect();
+    JsError::new(&format!("Invalid ruleset: {}", problems.join("; ")))
+}
+
+fn validated_rules(rules: JsValue) -> Result<BlackjackRuleset, JsError> {
+    let rules: BlackjackRuleset = from_js(rules, "ruleset")?;
+    rules.validate().map_err(ruleset_error)?;
+    Ok(rules)
+}
+
+// Bets are passed in dollars and kept in whole cents
+fn parse_bet(starting_bet: f64) -> Result<Money, JsError> {
+    let bet = Money::from(starting_bet);
+    if !starting_bet.is_finite() || bet <= Money::ZERO {
+        return Err(JsError::new(&format!(
+            "Invalid starting_bet: {} (expected a positive amount)",
+            starting_bet
+        )));
+    }
+    Ok(bet)
+}
+
+fn expect_state(game: &BlackjackState, expected: GameState) -> Result<(), JsError> {
+    if std::mem::discriminant(&game.state) != std::mem::discriminant(&expected) {
+        return Err(JsError::new(&format!(
+            "Invalid state: expected {:?} but game is in {:?}",
+            expected, game.state
+        )));
+    }
+    Ok(())
+}
+
+fn expect_hand_index(game: &BlackjackState) -> Result<(), JsError> {
+    if game.hand_index >= game.player_hands.len() {
+        return Err(JsError::new(&format!(
+            "Invalid hand_index: {} (player has {} hands)",
+            game.hand_index,
+            game.player_hands.len()
+        )));
+    }
+    Ok(())
+}
+
+// The player's turn, or the Blackjack Switch decision before it
+fn expect_player_decision(game: &BlackjackState) -> Result<(), JsError> {
+    if !game.state.awaits_player_action() {
+        return Err(JsError::new(&format!(
+            "Invalid state: expected a player decision but game is in {:?}",
+            game.state
+        )));
+    }
+    expect_hand_index(game)?;
+    if matches!(game.state, GameState::PlayerTurn) && game.player_hand_finished(&game.player_hands)
+    {
+        return Err(JsError::new(&format!(
+            "Invalid state: hand {} is finished but game is in {:?}",
+            game.hand_index, game.state
+        )));
+    }
+    Ok(())
+}
 
 #[wasm_bindgen]
-pub fn init_state(starting_bet: f32, rules: JsValue) -> JsValue {
-    let rules: BlackjackRuleset = serde_wasm_bindgen::from_value(rules).unwrap();
-    let game = crate::blackjack::init_state(starting_bet, rules);
-    serde_wasm_bindgen::to_value(&game).unwrap()
+pub fn init_state(
+    starting_bet: f64,
+    rules: JsValue,
+    num_decks: Option<usize>,
+) -> Result<JsValue, JsError> {
+    let rules = validated_rules(rules)?;
+    let starting_bet = parse_bet(starting_bet)?;
+    let num_decks = num_decks.unwrap_or(crate::blackjack::NUM_DECKS);
+    if num_decks == 0 {
+        return Err(JsError::new(
+            "Invalid num_decks: the shoe needs at least one deck",
+        ));
+    }
+    let shoe = crate::blackjack::init_shoe(rules.variant, num_decks);
+    let game =
+        crate::blackjack::init_state_with_shoe(starting_bet, rules, shoe).map_err(ruleset_error)?;
+    to_js(&game)
 }
 
+// Starts a round with scripted cards on top of a shuffled shoe
 #[wasm_bindgen]
-pub fn next_state(game: JsValue, action: JsValue) -> JsValue {
-    let mut game: BlackjackState = serde_wasm_bindgen::from_value(game).unwrap();
-    let action: Option<PlayerAction> = if action == JsValue::UNDEFINED {
+pub fn init_scenario(
+    starting_bet: f64,
+    rules: JsValue,
+    scenario: JsValue,
+    num_decks: Option<usize>,
+) -> Result<JsValue, JsError> {
+    let rules = validated_rules(rules)?;
+    let starting_bet = parse_bet(starting_bet)?;
+    // either a scenario object or text like "As Ah / Tc 7d"
+    let scenario: Scenario = match scenario.as_string() {
+        Some(text) => text
+            .parse()
+            .map_err(|err| JsError::new(&format!("Invalid scenario: {}", err)))?,
+        None => from_js(scenario, "scenario")?,
+    };
+    scenario
+        .validate()
+        .map_err(|problem| JsError::new(&format!("Invalid scenario: {}", problem)))?;
+    let num_decks = num_decks.unwrap_or(crate::blackjack::NUM_DECKS);
+    if num_decks == 0 {
+        return Err(JsError::new(
+            "Invalid num_decks: the shoe needs at least one deck",
+        ));
+    }
+    let game = scenario
+        .build(starting_bet, rules, num_decks)
+        .map_err(ruleset_error)?;
+    to_js(&game)
+}
+
+#[wasm_bindgen]
+pub fn parse_cards(text: &str) -> Result<JsValue, JsError> {
+    let cards = notation::parse_cards(text)
+        .map_err(|err| JsError::new(&format!("Invalid cards: {}", err)))?;
+    to_js(&cards)
+}
+
+#[wasm_bindgen]
+pub fn format_cards(cards: JsValue, ascii: bool) -> Result<String, JsError> {
+    let cards: Vec<Card> = from_js(cards, "cards")?;
+    let format = match ascii {
+        true => notation::CardFormat::ASCII,
+        false => notation::CardFormat::UNICODE,
+    };
+    Ok(notation::format_cards(&cards, format))
+}
+
+#[wasm_bindgen]
+pub fn validate_ruleset(rules: JsValue) -> Result<JsValue, JsError> {
+    let rules: BlackjackRuleset = from_js(rules, "ruleset")?;
+    let problems = rules.validate().err().unwrap_or_default();
+    to_js(&problems)
+}
+
+#[wasm_bindgen]
+pub fn normalize_ruleset(rules: JsValue) -> Result<JsValue, JsError> {
+    let rules: BlackjackRuleset = from_js(rules, "ruleset")?;
+    to_js(&rules.normalize())
+}
+
+#[wasm_bindgen]
+pub fn estimate_house_edge(rules: JsValue, num_decks: Option<usize>) -> Result<JsValue, JsError> {
+    let rules: BlackjackRuleset = from_js(rules, "ruleset")?;
+    let num_decks = num_decks.unwrap_or(crate::blackjack::NUM_DECKS);
+    to_js(&house_edge::estimate_house_edge(&rules, num_decks))
+}
+
+#[wasm_bindgen]
+pub fn get_ruleset_presets() -> Result<JsValue, JsError> {
+    to_js(&presets::PRESETS)
+}
+
+#[wasm_bindgen]
+pub fn get_ruleset_preset(name: &str) -> Result<JsValue, JsError> {
+    let preset = presets::find_preset(name).ok_or_else(|| {
+        let names: Vec<&str> = presets::PRESETS.iter().map(|preset| preset.name).collect();
+        JsError::new(&format!(
+            "Unknown ruleset preset: {}. Available presets are {:?}",
+            name, names
+        ))
+    })?;
+    to_js(preset)
+}
+
+fn out_of_cards_error(err: OutOfCards) -> JsError {
+    JsError::new(&format!("Invalid game state: {}", err))
+}
+
+// Parses the game and action and checks that the action can be applied
+fn parse_step(
+    game: JsValue,
+    action: JsValue,
+) -> Result<(BlackjackState, Option<PlayerAction>), JsError> {
+    let game: BlackjackState = from_js(game, "game state")?;
+    let action: Option<PlayerAction> = if action.is_undefined() || action.is_null() {
         None
     } else {
-        let action: PlayerAction = serde_wasm_bindgen::from_value(action).unwrap();
-        Some(action)
+        Some(from_js(action, "action")?)
     };
-    game.next_state(action);
-    serde_wasm_bindgen::to_value(&game).unwrap()
+    match (game.state, action) {
+        (GameState::GameOver, _) => {
+            return Err(JsError::new("Game is over; no more actions allowed."));
+        }
+        (GameState::SwitchDecision | GameState::PlayerTurn, None) => {
+            return Err(JsError::new("An action is required on the player's turn."));
+        }
+        (GameState::SwitchDecision | GameState::PlayerTurn, Some(action)) => {
+            expect_player_decision(&game)?;
+            if !game.is_action_allowed(action) {
+                let allowed_actions = game.allowed_actions();
+                return Err(JsError::new(&format!(
+                    "Invalid action: {:?}. Valid actions are {:?}",
+                    action, allowed_actions
+                )));
+            }
+        }
+        (_, Some(action)) => {
+            return Err(JsError::new(&format!(
+                "Invalid action: {:?}. No action is allowed during {:?}",
+                action, game.state
+            )));
+        }
+        (_, None) => {}
+    }
+    Ok((game, action))
+}
+
+#[wasm_bindgen]
+pub fn next_state(game: JsValue, action: JsValue) -> Result<JsValue, JsError> {
+    let (mut game, action) = parse_step(game, action)?;
+    game.try_next_state(action).map_err(out_of_cards_error)?;
+    to_js(&game)
+}
+
+#[derive(Serialize)]
+struct StepWithEvents {
+    state: BlackjackState,
+    events: Vec<GameEvent>,
+}
+
+#[wasm_bindgen]
+pub fn next_state_with_events(game: JsValue, action: JsValue) -> Result<JsValue, JsError> {
+    let (mut game, action) = parse_step(game, action)?;
+    let events = game
+        .try_next_state_with_events(action)
+        .map_err(out_of_cards_error)?;
+    to_js(&StepWithEvents {
+        state: game,
+        events,
+    })
+}
+
+#[wasm_bindgen]
+pub fn place_side_bet(game: JsValue, side_bet: JsValue) -> Result<JsValue, JsError> {
+    let mut game: BlackjackState = from_js(game, "game state")?;
+    let side_bet: SideBet = from_js(side_bet, "side bet")?;
+    game.place_side_bet(side_bet)
+        .map_err(|problem| JsError::new(&problem))?;
+    to_js(&game)
+}
+
+#[wasm_bindgen]
+pub fn get_default_paytable(kind: JsValue) -> Result<JsValue, JsError> {
+    let kind: SideBetKind = from_js(kind, "side bet kind")?;
+    to_js(kind.default_paytable())
 }
 
 #[wasm_bindgen]
-pub fn get_allowed_actions(game: JsValue) -> JsValue {
-    let game: BlackjackState = serde_wasm_bindgen::from_value(game).unwrap();
-    serde_wasm_bindgen::to_value(&game.allowed_actions()).unwrap()
+pub fn enable_undo(game: JsValue) -> Result<JsValue, JsError> {
+    let mut game: BlackjackState = from_js(game, "game state")?;
+    game.enable_undo();
+    to_js(&game)
 }
 
 #[wasm_bindgen]
-pub fn get_optimal_move(game: JsValue) -> JsValue {
-    let game: BlackjackState = serde_wasm_bindgen::from_value(game).unwrap();
+pub fn get_undo_depth(game: JsValue) -> Result<usize, JsError> {
+    let game: BlackjackState = from_js(game, "game state")?;
+    Ok(game.undo_depth())
+}
+
+#[wasm_bindgen]
+pub fn undo(game: JsValue) -> Result<JsValue, JsError> {
+    let mut game: BlackjackState = from_js(game, "game state")?;
+    if !game.undo() {
+        return Err(JsError::new(
+            "Nothing to undo. Call enable_undo before the player's first decision.",
+        ));
+    }
+    to_js(&game)
+}
+
+#[wasm_bindgen]
+pub fn rewind_to(game: JsValue, depth: usize) -> Result<JsValue, JsError> {
+    let mut game: BlackjackState = from_js(game, "game state")?;
+    let undo_depth = game.undo_depth();
+    if !game.rewind_to(depth) {
+        return Err(JsError::new(&format!(
+            "Invalid depth: {} (game has {} decisions to rewind)",
+            depth, undo_depth
+        )));
+    }
+    to_js(&game)
+}
+
+#[wasm_bindgen]
+pub fn get_allowed_actions(game: JsValue) -> Result<JsValue, JsError> {
+    let game: BlackjackState = from_js(game, "game state")?;
+    expect_player_decision(&game)?;
+    to_js(&game.allowed_actions())
+}
+
+// The most the current hand can be doubled for with a `DoubleFor` action, or
+// null when doubling for less isn't possible
+#[wasm_bindgen]
+pub fn get_max_double_for(game: JsValue) -> Result<JsValue, JsError> {
+    let game: BlackjackState = from_js(game, "game state")?;
+    expect_state(&game, GameState::PlayerTurn)?;
+    expect_player_decision(&game)?;
+    to_js(&game.max_double_for())
+}
+
+#[wasm_bindgen]
+pub fn get_optimal_move(game: JsValue) -> Result<JsValue, JsError> {
+    let game: BlackjackState = from_js(game, "game state")?;
+    expect_player_decision(&game)?;
     let optimal_move = game.get_optimal_move();
-    serde_wasm_bindgen::to_value(&optimal_move).unwrap()
+    to_js(&optimal_move)
 }
 
 #[wasm_bindgen]
-pub fn get_player_hand_value(game: JsValue) -> JsValue {
-    let game: BlackjackState = serde_wasm_bindgen::from_value(game).unwrap();
-    let player_hand = &game.player_hands[game.hand_index];
-    let aces_split = game.player_split_aces(&game.player_hands);
-    let player_hand_value = game.player_hand_value(player_hand, aces_split);
-    serde_wasm_bindgen::to_value(&player_hand_value).unwrap()
+pub fn get_player_hand_value(game: JsValue) -> Result<JsValue, JsError> {
+    let game: BlackjackState = from_js(game, "game state")?;
+    expect_hand_index(&game)?;
+    let player_hand_value = game.player_hand_value(&game.player_hands[game.hand_index]);
+    to_js(&player_hand_value)
 }
 
 #[wasm_bindgen]
-pub fn get_dealer_hand_value(game: JsValue) -> JsValue {
-    let game: BlackjackState = serde_wasm_bindgen::from_value(game).unwrap();
+pub fn get_dealer_hand_value(game: JsValue) -> Result<JsValue, JsError> {
+    let game: BlackjackState = from_js(game, "game state")?;
     let dealer_hand_value = game.dealer_hand_value(&game.dealer_hand, false);
-    serde_wasm_bindgen::to_value(&dealer_hand_value).unwrap()
+    to_js(&dealer_hand_value)
 }
 
 #[wasm_bindgen]
-pub fn get_game_outcome(game: JsValue) -> JsValue {
-    let game: BlackjackState = serde_wasm_bindgen::from_value(game).unwrap();
+pub fn get_game_outcome(game: JsValue) -> Result<JsValue, JsError> {
+    let game: BlackjackState = from_js(game, "game state")?;
+    expect_state(&game, GameState::GameOver)?;
     let player_hand_outcomes = game.player_hand_outcomes();
-    serde_wasm_bindgen::to_value(&player_hand_outcomes).unwrap()
+    to_js(&player_hand_outcomes)
 }
 
 #[wasm_bindgen]
-pub fn monte_carlo(rules: JsValue, iterations: u32) -> () {
-    let rules: BlackjackRuleset = serde_wasm_bindgen::from_value(rules).unwrap();
+pub fn monte_carlo(rules: JsValue, iterations: u32) -> Result<(), JsError> {
+    let rules = validated_rules(rules)?;
     for _ in 0..iterations {
-        let mut game = crate::blackjack::init_state(1f32, rules);
-        while !matches!(game.state, GameState::GameOver) {
-            if matches!(game.state, GameState::PlayerTurn) {
-                let player_action = game.get_optimal_move();
-                game.next_state(Some(player_action))
-            } else {
-                game.next_state(None)
-            }
-        }
+        let mut game =
+            crate::blackjack::init_state(Money::from_dollars(1), rules).map_err(ruleset_error)?;
+        crate::monte_carlo::play_optimal_round(&mut game);
     }
+    Ok(())
 }
 
 #[wasm_bindgen]
-pub fn simulate_dealer_stand_outcome(upcard: u8, iterations: u32) -> JsValue {
+pub fn simulate_side_bet_edge(
+    rules: JsValue,
+    side_bet: JsValue,
+    iterations: u32,
+) -> Result<f64, JsError> {
+    let rules = validated_rules(rules)?;
+    let side_bet: SideBet = from_js(side_bet, "side bet")?;
+    side_bet
+        .validate()
+        .map_err(|problem| JsError::new(&format!("Invalid side bet: {}", problem)))?;
+    if iterations == 0 {
+        return Err(JsError::new(
+            "Invalid iterations: expected at least one round",
+        ));
+    }
+    crate::monte_carlo::simulate_side_bet_edge(rules, &side_bet, iterations).map_err(ruleset_error)
+}
+
+#[wasm_bindgen]
+pub fn simulate_dealer_stand_outcome(upcard: u8, iterations: u32) -> Result<JsValue, JsError> {
+    if !(1..=10).contains(&upcard) {
+        return Err(JsError::new(&format!(
+            "Invalid upcard: {} (expected a value between 1 and 10)",
+            upcard
+        )));
+    }
     let results = crate::monte_carlo::simulate_dealer_stand_outcome(upcard, iterations);
-    serde_wasm_bindgen::to_value(&results).unwrap()
+    to_js(&results)
 }
 
 #[wasm_bindgen]
-pub fn install_debugging_hook() -> () {
+pub fn install_debugging_hook() {
     crate::debugging::set_panic_hook();
 }
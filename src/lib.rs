@@ -1,6 +1,18 @@
-mod blackjack;
+//! Blackjack game engine, rulesets, basic strategy and simulation.
+//!
+//! The types needed to play a round are re-exported here. Game variants,
+//! side bets, hand histories and the house edge estimate live under
+//! [`blackjack`]. The wasm-bindgen exports used by the web front end are not
+//! part of the Rust API.
+pub mod blackjack;
 mod debugging;
 mod js_bindings;
 pub mod monte_carlo;
 
-pub use js_bindings::*;
+pub use blackjack::{
+    init_shoe, init_state, init_state_with_shoe,
+    money::{Money, Payout},
+    ruleset::{BlackjackRuleset, GameVariant, RulesetProblem},
+    BlackjackState, Card, GameState, HandOutcome, OutOfCards, PlayerAction,
+};
+pub use monte_carlo::{play_optimal_round, simulate_house_edge};
@@ -1,16 +1,28 @@
-mod blackjack;
+mod config;
 mod terminal;
 
-use blackjack::{
-    init_state,
-    ruleset::{BlackjackRuleset, DoubleDownOn, MaxHandsAfterSplit, SplitAces},
-    BlackjackState, GameState, HandOutcome, LossReason, PlayerAction, WinReason,
+use blackjack_analyzer_rs::blackjack::{
+    self,
+    history::{read_histories, write_history, HandRecorder, Replay},
+    house_edge::estimate_house_edge,
+    next_shoe,
+    notation::{format_cards, CardFormat},
+    presets::{find_preset, PRESETS},
+    side_bets::{SideBet, SideBetOutcome},
+    LossReason, WinReason,
 };
-use blackjack_analyzer_rs::monte_carlo::simulate_dealer_stand_outcome;
+use blackjack_analyzer_rs::monte_carlo::{round_net, side_bets_net, simulate_dealer_stand_outcome};
+use blackjack_analyzer_rs::{
+    init_shoe, init_state_with_shoe, play_optimal_round, BlackjackState, HandOutcome, Money,
+    PlayerAction,
+};
+use config::{load_config, save_config, Config, Settings, ShoeConfig};
 use num_format::{Locale, ToFormattedString};
 use std::{
     collections::HashMap,
-    io::{self, Write},
+    fs::{File, OpenOptions},
+    io::{self, BufReader, Write},
+    path::Path,
     sync::mpsc,
     thread,
     time::{Duration, SystemTime},
@@ -41,7 +53,41 @@ fn get_title_screen_input() -> TitleScreenInput {
     }
 }
 
-fn get_player_input(allowed_actions: &Vec<PlayerAction>) -> blackjack::PlayerAction {
+enum PlayerInput {
+    Action(PlayerAction),
+    Undo,
+}
+// Asks how much to double for, up to `max` (the hand's bet)
+fn get_double_amount(max: Money) -> PlayerAction {
+    print!(
+        "Double for how much? (up to ${}, Enter for the full amount): ",
+        max
+    );
+    let _ = io::stdout().flush();
+    let mut input = String::new();
+    let _ = io::stdin().read_line(&mut input);
+    if input.trim().is_empty() {
+        return PlayerAction::DoubleDown;
+    }
+    match input.trim().trim_start_matches('$').parse::<f64>() {
+        Ok(dollars) if Money::from(dollars) == max => PlayerAction::DoubleDown,
+        Ok(dollars) if Money::from(dollars) > Money::ZERO && Money::from(dollars) < max => {
+            PlayerAction::DoubleFor(Money::from(dollars))
+        }
+        _ => {
+            println!("Invalid amount. Please try again.");
+            get_double_amount(max)
+        }
+    }
+}
+
+// `max_double` is the most the hand can double for when doubling for less is
+// allowed
+fn get_player_input(
+    allowed_actions: &Vec<PlayerAction>,
+    can_undo: bool,
+    max_double: Option<Money>,
+) -> PlayerInput {
     for (i, action) in allowed_actions.iter().enumerate() {
         println!(
             "{}: {}",
@@ -50,42 +96,127 @@ fn get_player_input(allowed_actions: &Vec<PlayerAction>) -> blackjack::PlayerAct
                 PlayerAction::Hit => "Hit",
                 PlayerAction::Stand => "Stand",
                 PlayerAction::DoubleDown => "Double Down",
+                PlayerAction::DoubleFor(_) => "Double For Less",
                 PlayerAction::Split => "Split",
                 PlayerAction::Surrender => "Surrender",
+                PlayerAction::Switch => "Switch",
+                PlayerAction::Twist => "Twist",
+                PlayerAction::Stick => "Stick",
+                PlayerAction::Buy => "Buy",
             }
         );
     }
+    if can_undo {
+        println!("U: Undo");
+    }
     print!("Please enter your move: ");
     let _ = io::stdout().flush(); // Make sure the prompt is immediately displayed
     let mut input = String::new();
     let _ = io::stdin().read_line(&mut input);
+    if can_undo && input.trim().eq_ignore_ascii_case("u") {
+        return PlayerInput::Undo;
+    }
     match input.trim().parse::<usize>() {
-        Ok(n) if n >= 1 && n <= allowed_actions.len() => allowed_actions[n - 1],
+        Ok(n) if n >= 1 && n <= allowed_actions.len() => match (allowed_actions[n - 1], max_double)
+        {
+            (PlayerAction::DoubleDown, Some(max)) => PlayerInput::Action(get_double_amount(max)),
+            (action, _) => PlayerInput::Action(action),
+        },
         _ => {
             println!("Invalid input. Please try again.");
-            get_player_input(allowed_actions)
+            get_player_input(allowed_actions, can_undo, max_double)
         }
     }
 }
 
-const RULES: BlackjackRuleset = BlackjackRuleset {
-    surrender: true,
-
-    dealer_stands_on_all_17: true,
-    dealer_peeks: true,
-
-    split_aces: SplitAces::Twice,
-    hit_on_split_ace: false,
-    max_hands_after_split: MaxHandsAfterSplit::Three,
-
-    double_down_on: DoubleDownOn::Any,
-    double_after_split: true,
-    double_on_split_ace: false,
-
-    ace_and_ten_counts_as_blackjack: true,
-    blackjack_payout: 3.0 / 2.0,
-    split_ace_can_be_blackjack: false,
-};
+struct CliOptions {
+    settings: Settings,
+    show_house_edge: bool,
+    record_path: Option<String>,
+    replay_path: Option<String>,
+}
+fn parse_args() -> Result<CliOptions, String> {
+    let mut config = Config::default();
+    let mut save_path: Option<String> = None;
+    let mut show_house_edge = false;
+    let mut record_path: Option<String> = None;
+    let mut replay_path: Option<String> = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rules" => {
+                let name = args.next().ok_or("--rules requires a preset name")?;
+                if find_preset(&name).is_none() {
+                    return Err(format!(
+                        "Unknown ruleset preset: {}. Use --list-rules to see available presets.",
+                        name
+                    ));
+                }
+                config.preset = Some(name);
+                config.rules = None;
+            }
+            "--config" => {
+                let path = args.next().ok_or("--config requires a file path")?;
+                config = load_config(Path::new(&path)).map_err(|err| err.to_string())?;
+            }
+            "--save-config" => {
+                let path = args.next().ok_or("--save-config requires a file path")?;
+                save_path = Some(path);
+            }
+            "--house-edge" => show_house_edge = true,
+            "--scenario" => {
+                let text = args
+                    .next()
+                    .ok_or("--scenario requires cards, eg. \"As Ah / Tc\"")?;
+                let scenario = text
+                    .parse()
+                    .map_err(|err| format!("Invalid scenario: {}", err))?;
+                config.scenario = Some(scenario);
+            }
+            "--record" => {
+                let path = args.next().ok_or("--record requires a file path")?;
+                record_path = Some(path);
+            }
+            "--replay" => {
+                let path = args.next().ok_or("--replay requires a file path")?;
+                replay_path = Some(path);
+            }
+            "--list-rules" => {
+                for preset in PRESETS.iter() {
+                    println!(
+                        "{:<16} {}: {}",
+                        preset.name, preset.title, preset.description
+                    );
+                }
+                std::process::exit(0);
+            }
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    let settings = config.resolve().map_err(|err| err.to_string())?;
+    if let Some(path) = save_path {
+        let config = Config {
+            preset: None,
+            rules: Some(settings.rules),
+            shoe: ShoeConfig {
+                num_decks: Some(settings.num_decks),
+                penetration: settings.penetration,
+            },
+            simulation: settings.simulation,
+            scenario: settings.scenario.clone(),
+            side_bets: settings.side_bets.clone(),
+        };
+        save_config(Path::new(&path), &config).map_err(|err| err.to_string())?;
+        println!("Saved config to {}", path);
+        std::process::exit(0);
+    }
+    Ok(CliOptions {
+        settings,
+        show_house_edge,
+        record_path,
+        replay_path,
+    })
+}
 
 fn print_game_state(game: &BlackjackState) {
     print!("Dealer hand:");
@@ -102,12 +233,27 @@ fn print_game_state(game: &BlackjackState) {
     for (i, hand) in game
         .player_hands
         .iter()
-        .filter(|&h| !h.is_empty())
+        .filter(|&h| !h.cards.is_empty())
         .enumerate()
     {
         print!("Player hand:");
-        for card in hand {
-            print!(" {}", card.rank.to_string());
+        for card in &hand.cards {
+            print!(" {}", card.rank);
+        }
+        if hand.doubled {
+            print!(" (doubled)");
+        }
+        if hand.switched {
+            print!(" (switched)");
+        }
+        if hand.bought > 0 {
+            print!(" (bought {})", hand.bought);
+        }
+        if hand.free_bet > Money::ZERO {
+            print!(" (free bet ${})", hand.free_bet);
+        }
+        if hand.surrendered {
+            print!(" (surrendered)");
         }
         if i == game.hand_index {
             print!("{}", yellow(" ←"));
@@ -116,16 +262,56 @@ fn print_game_state(game: &BlackjackState) {
     }
 }
 
+const EVENT_LOG_LINES: usize = 4;
+fn print_event_log(event_log: &[String]) {
+    println!();
+    for line in event_log
+        .iter()
+        .skip(event_log.len().saturating_sub(EVENT_LOG_LINES))
+    {
+        println!("  {}", line);
+    }
+    println!();
+}
+
 fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    if let Some(path) = &options.replay_path {
+        replay_histories(path);
+        return;
+    }
     println!("Welcome to Blackjack!");
+    println!(
+        "Rules: {} ({})",
+        options.settings.title, options.settings.description
+    );
+    let estimate = estimate_house_edge(&options.settings.rules, options.settings.num_decks);
+    if options.show_house_edge {
+        for rule_effect in &estimate.breakdown {
+            println!(
+                "  {:<32} {:+.2}%",
+                rule_effect.rule,
+                rule_effect.effect * 100f64
+            );
+        }
+    }
+    println!("Estimated house edge: {:.2}%", estimate.house_edge * 100f64);
     println!("1: Play game");
     println!("2: Auto play");
     println!("3: Monte Carlo Simulation");
     println!("4: Performance test");
     match get_title_screen_input() {
-        TitleScreenInput::PlayGame => play(false),
-        TitleScreenInput::AutoPlay => play(true),
-        TitleScreenInput::MonteCarloSimulation => monte_carlo_simulation(),
+        TitleScreenInput::PlayGame => {
+            play(false, &options.settings, options.record_path.as_deref())
+        }
+        TitleScreenInput::AutoPlay => play(true, &options.settings, options.record_path.as_deref()),
+        TitleScreenInput::MonteCarloSimulation => monte_carlo_simulation(&options.settings),
         TitleScreenInput::PerformanceTest => {
             let iterations = 2_000_000;
             let start_time = std::time::Instant::now();
@@ -138,180 +324,305 @@ fn main() {
     }
 }
 
-const FLAT_BET: f32 = 1f32;
-fn play(auto_play: bool) {
-    let mut bankroll = 1000f32;
+fn play(auto_play: bool, settings: &Settings, record_path: Option<&str>) {
+    let flat_bet = settings.simulation.flat_bet;
+    let mut bankroll = Money::from_dollars(1000);
+    let mut record_file = record_path.map(|path| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap_or_else(|err| {
+                eprintln!("Could not open {}: {}", path, err);
+                std::process::exit(1);
+            })
+    });
+    let mut shoe = init_shoe(settings.rules.variant, settings.num_decks);
     loop {
         let starting_balance = bankroll;
-        bankroll -= FLAT_BET;
-        let mut game = init_state(FLAT_BET, RULES);
+        shoe = match &settings.scenario {
+            Some(scenario) => scenario.shoe(settings.rules.variant, settings.num_decks),
+            None => next_shoe(
+                shoe,
+                settings.rules.variant,
+                settings.num_decks,
+                settings.penetration,
+            ),
+        };
+        let mut game = init_state_with_shoe(flat_bet, settings.rules, shoe)
+            .expect("Settings rules are validated");
+        for side_bet in &settings.side_bets {
+            game.place_side_bet(side_bet.clone())
+                .expect("Settings side bets are validated");
+        }
+        if !auto_play {
+            game.enable_undo();
+        }
+        let mut event_log: Vec<String> = Vec::new();
+        let mut recorder = HandRecorder::new(&game);
 
-        while !matches!(game.state, blackjack::GameState::GameOver) {
-            clear_screen();
-            print_game_state(&game);
-            match game.state {
-                blackjack::GameState::Dealing | blackjack::GameState::DealerTurn => {
-                    thread::sleep(Duration::from_millis(150));
-                    game.next_state(None);
-                }
-                blackjack::GameState::PlayerTurn => {
-                    let allowed_actions = game.allowed_actions();
-                    let player_action = match auto_play {
-                        true => game.get_optimal_move(),
-                        false => get_player_input(&allowed_actions),
-                    };
-                    if matches!(
-                        player_action,
-                        PlayerAction::DoubleDown | PlayerAction::Split
-                    ) {
-                        bankroll -= FLAT_BET;
+        loop {
+            while !matches!(game.state, blackjack::GameState::GameOver) {
+                clear_screen();
+                print_game_state(&game);
+                print_event_log(&event_log);
+                match game.state {
+                    blackjack::GameState::Dealing | blackjack::GameState::DealerTurn => {
+                        thread::sleep(Duration::from_millis(150));
+                        game.next_state_with_listener(None, |event| {
+                            event_log.push(event.to_string())
+                        });
                     }
-                    game.next_state(Some(player_action));
+                    blackjack::GameState::SwitchDecision | blackjack::GameState::PlayerTurn => {
+                        let allowed_actions = game.allowed_actions();
+                        let input = match auto_play {
+                            true => PlayerInput::Action(game.get_optimal_move()),
+                            false => get_player_input(
+                                &allowed_actions,
+                                game.undo_depth() > 0,
+                                game.max_double_for(),
+                            ),
+                        };
+                        match input {
+                            PlayerInput::Action(player_action) => {
+                                recorder.record_action(player_action);
+                                game.next_state_with_listener(Some(player_action), |event| {
+                                    event_log.push(event.to_string())
+                                });
+                            }
+                            PlayerInput::Undo => {
+                                game.undo();
+                                recorder.rewind_to(&game);
+                                event_log.push("Player takes back the last decision".to_string());
+                            }
+                        }
+                    }
+                    blackjack::GameState::GameOver => panic!("Unreachable code."),
                 }
-                blackjack::GameState::GameOver => panic!("Unreachable code."),
             }
-        }
-        clear_screen();
-        print_game_state(&game);
-        let earnings = {
-            let player_hand_outcomes = game.player_hand_outcomes();
-            let mut earnings = 0f32;
-            for (bet, outcome) in game.bets.iter().zip(player_hand_outcomes) {
-                earnings += match outcome {
+            clear_screen();
+            print_game_state(&game);
+            print_event_log(&event_log);
+            let pontoon = game.is_pontoon();
+            for outcome in game.player_hand_outcomes() {
+                match outcome {
+                    HandOutcome::Won(WinReason::Blackjack) if pontoon => {
+                        println!("{}", green("Pontoon!"))
+                    }
                     HandOutcome::Won(WinReason::Blackjack) => {
-                        println!("{}", green("Blackjack!"));
-                        game.rules.blackjack_payout * (*bet * 2f32)
+                        println!("{}", green("Blackjack!"))
                     }
                     HandOutcome::Won(WinReason::DealerBust) => {
-                        println!("{}", green("Dealer busts!"));
-                        *bet * 2f32
+                        println!("{}", green("Dealer busts!"))
                     }
                     HandOutcome::Won(WinReason::HigherHand) => {
-                        println!("{}", green("Player Wins!"));
-                        *bet * 2f32
+                        println!("{}", green("Player Wins!"))
+                    }
+                    HandOutcome::Won(WinReason::TwentyOne) => {
+                        println!("{}", green("Player 21 wins!"))
                     }
-                    HandOutcome::Push => {
-                        println!("{}", yellow("Push."));
-                        *bet
+                    HandOutcome::Won(WinReason::Charlie) => {
+                        println!("{}", green("Charlie!"))
                     }
-                    HandOutcome::Lost(LossReason::Bust) => {
-                        println!("{}", red("Bust."));
-                        0f32
+                    HandOutcome::Won(WinReason::FiveCardTrick) => {
+                        println!("{}", green("Five-card trick!"))
                     }
+                    HandOutcome::Won(WinReason::Bonus(bonus)) => println!(
+                        "{}",
+                        green(format!("Bonus for {} ({})!", bonus, bonus.payout()).as_str())
+                    ),
+                    HandOutcome::Push => println!("{}", yellow("Push.")),
+                    HandOutcome::Lost(LossReason::Bust) => println!("{}", red("Bust.")),
                     HandOutcome::Lost(LossReason::LowerHand) => {
-                        println!("{}", red("Dealer wins."));
-                        0f32
+                        println!("{}", red("Dealer wins."))
+                    }
+                    HandOutcome::Lost(LossReason::DealerBlackjack) if pontoon => {
+                        println!("{}", red("Dealer has pontoon."))
                     }
                     HandOutcome::Lost(LossReason::DealerBlackjack) => {
-                        println!("{}", red("Dealer has blackjack."));
-                        0f32
+                        println!("{}", red("Dealer has blackjack."))
                     }
-                    HandOutcome::Surrendered => {
-                        println!("{}", yellow("Surrendered."));
-                        *bet / 2f32
+                    HandOutcome::Lost(LossReason::Tie) => {
+                        println!("{}", red("Dealer wins the tie."))
                     }
+                    HandOutcome::Surrendered => println!("{}", yellow("Surrendered.")),
                 }
             }
-            earnings
-        };
-        bankroll += earnings;
-        println!(
-            "Bankroll: ${:.2} {}",
-            bankroll,
-            if bankroll > starting_balance {
-                green(format!("(+${:.2})", bankroll - starting_balance).as_str())
-            } else {
-                red(format!("(-${:.2})", starting_balance - bankroll).as_str())
+            for side_bet in &game.side_bets {
+                match side_bet.outcome {
+                    Some(SideBetOutcome::Won { hand, payout }) => println!(
+                        "{}",
+                        green(
+                            format!("{} wins with {:?} ({})!", side_bet.kind, hand, payout)
+                                .as_str()
+                        )
+                    ),
+                    _ => println!("{}", red(format!("{} loses.", side_bet.kind).as_str())),
+                }
+            }
+            // free bets are paid for by the house, so only the net counts
+            bankroll = starting_balance + round_net(&game) + side_bets_net(&game);
+            println!(
+                "Bankroll: ${} {}",
+                bankroll,
+                if bankroll > starting_balance {
+                    green(format!("(+${})", bankroll - starting_balance).as_str())
+                } else {
+                    red(format!("(-${})", starting_balance - bankroll).as_str())
+                }
+            );
+            let can_undo = game.undo_depth() > 0;
+            match can_undo {
+                true => print!("Press Enter to play again, or U to undo:"),
+                false => print!("Press Enter to play again:"),
+            }
+            let _ = io::stdout().flush(); // Make sure the prompt is immediately displayed
+            let mut input = String::new();
+            let _ = io::stdin().read_line(&mut input);
+            if can_undo && input.trim().eq_ignore_ascii_case("u") {
+                game.undo();
+                recorder.rewind_to(&game);
+                event_log.push("Player takes back the last decision".to_string());
+                continue;
             }
+            break;
+        }
+        if let Some(file) = &mut record_file {
+            if let Err(err) = write_history(file, &recorder.finish(&game)) {
+                eprintln!("Could not record hand: {}", err);
+            }
+        }
+        shoe = game.shoe;
+    }
+}
+
+fn replay_histories(path: &str) {
+    let histories = match File::open(path)
+        .map_err(|err| err.to_string())
+        .and_then(|file| read_histories(BufReader::new(file)))
+    {
+        Ok(histories) => histories,
+        Err(err) => {
+            eprintln!("Could not read hand histories from {}: {}", path, err);
+            std::process::exit(1);
+        }
+    };
+    let mut mismatches = 0;
+    for (i, history) in histories.iter().enumerate() {
+        println!(
+            "Round {}: {}",
+            i + 1,
+            format_cards(&history.cards, CardFormat::ASCII)
         );
-        print!("Press Enter to play again:");
-        let _ = io::stdout().flush(); // Make sure the prompt is immediately displayed
-        let mut input = String::new();
-        let _ = io::stdin().read_line(&mut input);
+        let mut replay = match Replay::new(history) {
+            Ok(replay) => replay,
+            Err(err) => {
+                mismatches += 1;
+                println!("{}", red(format!("Replay failed: {}", err).as_str()));
+                continue;
+            }
+        };
+        let mut result = Ok(());
+        while !replay.finished() && result.is_ok() {
+            result = replay.step().map(|events| {
+                for event in events {
+                    println!("  {}", event);
+                }
+            });
+        }
+        match result.and_then(|_| replay.verify()) {
+            Ok(()) => {
+                print_game_state(replay.state());
+                println!("{}", green(format!("Net: {:+}", history.net).as_str()));
+            }
+            Err(err) => {
+                mismatches += 1;
+                println!("{}", red(format!("Replay failed: {}", err).as_str()));
+            }
+        }
+        println!();
     }
+    println!(
+        "Replayed {} round(s), {} failed verification.",
+        histories.len(),
+        mismatches
+    );
 }
 
-const NUM_THREADS: usize = 16;
 const TX_INTERVAL: Duration = Duration::from_millis(1000 / 160);
-fn monte_carlo_simulation() {
+fn monte_carlo_simulation(settings: &Settings) {
     let (tx, rx) = mpsc::channel();
+    let num_threads = settings.simulation.threads;
+    let flat_bet = settings.simulation.flat_bet;
 
-    for i in 0..16 {
+    for i in 0..num_threads {
         let thread_tx = tx.clone();
+        let settings = settings.clone();
         thread::spawn(move || {
             let mut send_time = SystemTime::now();
             // staggered start times
             thread::sleep(Duration::from_millis(
-                (i as f32 * ((TX_INTERVAL.as_millis() as f32) / (NUM_THREADS as f32))) as u64,
+                (i as f32 * ((TX_INTERVAL.as_millis() as f32) / (num_threads as f32))) as u64,
             ));
+            let mut net_earnings_distribution: HashMap<i32, u32> = HashMap::new();
+            let mut side_bet_nets = vec![Money::ZERO; settings.side_bets.len()];
+            let mut i = 1;
+            let mut shoe = init_shoe(settings.rules.variant, settings.num_decks);
             loop {
-                let mut net_earnings_distribution: HashMap<i32, u32> = HashMap::new();
-                let mut i = 1;
-                loop {
-                    let mut bankroll = 0f32;
-                    // let preround_bankroll = 0;
-                    bankroll -= FLAT_BET;
-                    let mut game = init_state(FLAT_BET, RULES);
-
-                    while !matches!(game.state, GameState::GameOver) {
-                        if matches!(game.state, GameState::PlayerTurn) {
-                            let player_action = game.get_optimal_move();
-                            if matches!(
-                                player_action,
-                                PlayerAction::DoubleDown | PlayerAction::Split
-                            ) {
-                                bankroll -= FLAT_BET;
-                            }
-                            game.next_state(Some(player_action))
-                        } else {
-                            game.next_state(None)
-                        }
-                    }
-                    let player_hand_outcomes = game.player_hand_outcomes();
-                    for (bet, outcome) in game.bets.iter().zip(player_hand_outcomes) {
-                        bankroll += match outcome {
-                            HandOutcome::Won(WinReason::Blackjack) => {
-                                *bet + game.rules.blackjack_payout * *bet
-                            }
-                            HandOutcome::Won(_) => *bet + *bet,
-                            HandOutcome::Push => *bet,
-                            HandOutcome::Lost(_) => 0f32,
-                            HandOutcome::Surrendered => *bet / 2f32,
-                        }
-                    }
-                    let net = bankroll;
-                    let net_cents = (net * 100f32).round() as i32;
-                    let zero: u32 = 0;
-                    net_earnings_distribution.insert(
-                        net_cents,
-                        net_earnings_distribution.get(&net_cents).unwrap_or(&zero) + 1,
-                    );
-                    if SystemTime::now()
-                        .duration_since(send_time)
-                        .unwrap_or(Duration::from_millis(1))
-                        > TX_INTERVAL
-                    {
-                        thread_tx
-                            .send((net_earnings_distribution.clone(), i))
-                            .unwrap();
-                        send_time = SystemTime::now();
-                        i = 1;
-                        net_earnings_distribution.clear();
-                    }
-                    i += 1;
+                shoe = next_shoe(
+                    shoe,
+                    settings.rules.variant,
+                    settings.num_decks,
+                    settings.penetration,
+                );
+                let mut game = init_state_with_shoe(flat_bet, settings.rules, shoe)
+                    .expect("Settings rules are validated");
+                for side_bet in &settings.side_bets {
+                    game.place_side_bet(side_bet.clone())
+                        .expect("Settings side bets are validated");
+                }
+                play_optimal_round(&mut game);
+                let net = round_net(&game);
+                for (total, side_bet) in side_bet_nets.iter_mut().zip(&game.side_bets) {
+                    *total += side_bet.net();
                 }
+                shoe = game.shoe;
+                let net_cents = net.cents() as i32;
+                let zero: u32 = 0;
+                net_earnings_distribution.insert(
+                    net_cents,
+                    net_earnings_distribution.get(&net_cents).unwrap_or(&zero) + 1,
+                );
+                if SystemTime::now()
+                    .duration_since(send_time)
+                    .unwrap_or(Duration::from_millis(1))
+                    > TX_INTERVAL
+                {
+                    thread_tx
+                        .send((net_earnings_distribution.clone(), side_bet_nets.clone(), i))
+                        .unwrap();
+                    send_time = SystemTime::now();
+                    i = 1;
+                    net_earnings_distribution.clear();
+                    side_bet_nets.fill(Money::ZERO);
+                }
+                i += 1;
             }
         });
     }
 
     let start_time = SystemTime::now();
     let mut net_earnings_distribution: HashMap<i32, u32> = HashMap::new();
+    let mut side_bet_nets = vec![Money::ZERO; settings.side_bets.len()];
     let mut iterations = 1;
     let mut last_print_time = SystemTime::now();
     // let mut j = 0;
     loop {
-        let (net_earnings_distribution2, i) = rx.recv().unwrap();
+        let (net_earnings_distribution2, side_bet_nets2, i) = rx.recv().unwrap();
         iterations += i;
+        for (total, net) in side_bet_nets.iter_mut().zip(side_bet_nets2) {
+            *total += net;
+        }
         for (key, value) in net_earnings_distribution2 {
             net_earnings_distribution = {
                 let mut map = net_earnings_distribution.clone();
@@ -326,12 +637,20 @@ fn monte_carlo_simulation() {
             > DRAW_INTERVAL
         {
             clear_screen();
-            print_stats(&start_time, &iterations, &net_earnings_distribution);
+            print_stats(
+                &start_time,
+                &iterations,
+                &net_earnings_distribution,
+                // Blackjack Switch starts with a bet on each hand
+                flat_bet * settings.rules.variant.starting_hands() as i64,
+                &settings.side_bets,
+                &side_bet_nets,
+            );
             last_print_time = SystemTime::now();
             if SystemTime::now()
                 .duration_since(start_time)
                 .unwrap_or(Duration::from_millis(1))
-                > Duration::from_secs(5)
+                > Duration::from_secs(settings.simulation.duration_secs)
             {
                 std::process::exit(0);
             }
@@ -343,6 +662,9 @@ fn print_stats(
     start_time: &SystemTime,
     iterations: &u32,
     net_earnings_distribution: &HashMap<i32, u32>,
+    round_wager: Money,
+    side_bets: &[SideBet],
+    side_bet_nets: &[Money],
 ) {
     // println!("Starting bankroll: ${}", *initial_bankroll);
     // let net = *bankroll - *initial_bankroll;
@@ -361,25 +683,25 @@ fn print_stats(
     // };
     println!("Loss/earnings distribution:");
     let mut vec = net_earnings_distribution.iter().collect::<Vec<_>>();
-    vec.sort_by(|a, b| a.0.cmp(&b.0));
-    let mut earnings: f64 = 0f64;
+    vec.sort_by(|a, b| a.0.cmp(b.0));
+    let mut earnings = Money::ZERO;
     for (cents, count) in vec {
-        let dollars = *cents as f64 / 100f64;
-        earnings += dollars as f64 * *count as f64;
+        let amount = Money::from_cents(*cents as i64);
+        earnings += amount * *count as i64;
 
         let percent = (*count as f64 / *iterations as f64) * 100f64;
         let count = (*count).to_formatted_string(&Locale::en);
         if *cents > 0 {
             println!(
                 "{}: {:.2}% ({})",
-                green(format!("+${:.2}", dollars.abs()).as_str()),
+                green(format!("+${}", amount).as_str()),
                 percent,
                 count
             )
         } else if *cents < 0 {
             println!(
                 "{}: {:.2}% ({})",
-                red(format!("-${:.2}", dollars.abs()).as_str()),
+                red(format!("-${}", -amount).as_str()),
                 percent,
                 count
             )
@@ -387,11 +709,20 @@ fn print_stats(
             println!("$0: {:.2}% ({})", percent, count)
         }
     }
-    let amount_wagered = *iterations as f64 * FLAT_BET as f64;
-    let house_edge = -(earnings / amount_wagered);
-    println!("Amount wagered: ${:.2}", amount_wagered);
-    println!("Net earnings: ${:.2}", earnings);
+    let amount_wagered = round_wager * *iterations as i64;
+    let house_edge = -(earnings.as_dollars() / amount_wagered.as_dollars());
+    println!("Amount wagered: ${}", amount_wagered);
+    println!("Net earnings: ${}", earnings);
     println!("House edge: {:.2}%", house_edge * 100f64);
+    for (side_bet, net) in side_bets.iter().zip(side_bet_nets) {
+        let wagered = side_bet.amount * *iterations as i64;
+        println!(
+            "{} house edge: {:.2}% (net {:+})",
+            side_bet.kind,
+            -(net.as_dollars() / wagered.as_dollars()) * 100f64,
+            net
+        );
+    }
     let duration = SystemTime::now()
         .duration_since(*start_time)
         .unwrap_or(Duration::from_millis(1));
@@ -1,8 +1,20 @@
+use crate::blackjack::{
+    init_shoe, init_state, init_state_with_shoe,
+    money::Money,
+    next_shoe,
+    ruleset::{BlackjackRuleset, RulesetProblem},
+    side_bets::SideBet,
+    BlackjackState, GameState,
+};
+// settling a round is part of the game engine
+pub use crate::blackjack::round_net;
 use rand::Rng;
 use std::collections::HashMap;
 
 const DECK: [u8; 13] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 10, 10, 10];
 const RNG_ARRAY_SIZE: usize = 1000;
+// how far into the shoe the cut card is placed when simulating a house edge
+const SIMULATION_PENETRATION: f32 = 0.75;
 
 fn hand_value(hand: u8, has_ace: bool) -> u8 {
     if has_ace && hand + 10 <= 21 {
@@ -42,9 +54,70 @@ pub fn simulate_dealer_stand_outcome(upcard: u8, iterations: u32) -> HashMap<u8,
     results
 }
 
+// Plays the rest of the round, always taking the basic strategy move
+pub fn play_optimal_round(game: &mut BlackjackState) {
+    while !matches!(game.state, GameState::GameOver) {
+        if game.state.awaits_player_action() {
+            let player_action = game.get_optimal_move();
+            game.next_state(Some(player_action))
+        } else {
+            game.next_state(None)
+        }
+    }
+}
+
+// Net winnings of the round's side bets, which are not part of `round_net`
+pub fn side_bets_net(game: &BlackjackState) -> Money {
+    game.side_bets.iter().map(|side_bet| side_bet.net()).sum()
+}
+
+// Plays `iterations` basic strategy rounds with the side bet placed and
+// returns its house edge
+pub fn simulate_side_bet_edge(
+    rules: BlackjackRuleset,
+    side_bet: &SideBet,
+    iterations: u32,
+) -> Result<f64, Vec<RulesetProblem>> {
+    let mut net = Money::ZERO;
+    for _ in 0..iterations {
+        let mut game = init_state(Money::from_dollars(1), rules)?;
+        game.place_side_bet(side_bet.clone())
+            .expect("Side bet is validated by the caller");
+        play_optimal_round(&mut game);
+        net += side_bets_net(&game);
+    }
+    Ok(-(net.as_dollars() / (side_bet.amount * iterations as i64).as_dollars()))
+}
+
+// Plays `iterations` basic strategy rounds from a `num_decks` shoe and
+// returns the house edge, per starting hand, with its standard error
+pub fn simulate_house_edge(
+    rules: BlackjackRuleset,
+    num_decks: usize,
+    iterations: u32,
+) -> Result<(f64, f64), Vec<RulesetProblem>> {
+    rules.validate()?;
+    let mut shoe = init_shoe(rules.variant, num_decks);
+    let (mut sum, mut sum_of_squares) = (0.0, 0.0);
+    for _ in 0..iterations {
+        shoe = next_shoe(shoe, rules.variant, num_decks, SIMULATION_PENETRATION);
+        let mut game = init_state_with_shoe(Money::from_dollars(1), rules, shoe)?;
+        play_optimal_round(&mut game);
+        let net = round_net(&game).as_dollars() / rules.variant.starting_hands() as f64;
+        sum += net;
+        sum_of_squares += net * net;
+        shoe = game.shoe;
+    }
+    let rounds = iterations.max(1) as f64;
+    let mean = sum / rounds;
+    let variance = (sum_of_squares / rounds - mean * mean).max(0.0);
+    Ok((-mean, (variance / rounds).sqrt()))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::simulate_dealer_stand_outcome;
+    use super::{simulate_dealer_stand_outcome, simulate_house_edge};
+    use crate::blackjack::presets::find_preset;
 
     #[test]
     fn test_simulate_dealer_stand_outcome() {
@@ -56,4 +129,18 @@ mod tests {
         dbg!(results);
         println!("Ran {:?} simulations in {:?}", iterations, duration);
     }
+
+    #[test]
+    fn test_simulate_house_edge() {
+        let preset = find_preset("vegas-strip").unwrap();
+        let (edge, error) = simulate_house_edge(preset.rules, preset.num_decks, 20_000).unwrap();
+        // a round's result has a standard deviation of about 1.15 bets
+        assert!((error - 1.15 / (20_000f64).sqrt()).abs() < 0.002);
+        assert!(edge.abs() < 5.0 * error);
+
+        let mut rules = preset.rules;
+        rules.double_after_split = false;
+        rules.double_on_split_ace = true;
+        assert!(simulate_house_edge(rules, preset.num_decks, 1).is_err());
+    }
 }